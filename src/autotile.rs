@@ -0,0 +1,170 @@
+//! Autotiling of coastlines: each tile's texture is made to agree with its
+//! classified type, with shore fringes and rounded corners synthesised from
+//! which of its eight neighbours sit lower.
+
+use crate::grid::Grid;
+use crate::map_generator::{clamp_to_band, TileType};
+use std::collections::HashMap;
+
+/// Neighbour offsets in bitmask order: N, NE, E, SE, S, SW, W, NW.
+const NEIGHBOURS: [(i64, i64); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// Radius of a rounded outer corner, as a fraction of the tile.
+const CORNER_RADIUS: f32 = 0.35;
+/// Radius of the notch cut where only a diagonal neighbour is lower.
+const NOTCH_RADIUS: f32 = 0.2;
+/// Width of the beach synthesised where land meets water directly.
+const FRINGE: f32 = 0.15;
+
+const WATER: u8 = 0;
+const SHORE: u8 = 1;
+const LAND: u8 = 2;
+
+fn level(tile: TileType) -> u8 {
+    match tile {
+        TileType::Ocean | TileType::Lake => WATER,
+        TileType::Shore => SHORE,
+        TileType::Land | TileType::River | TileType::Mountain | TileType::Cliff => LAND,
+    }
+}
+
+/// Levels of the eight neighbours in bitmask order; off-map neighbours
+/// count as the tile's own level so map edges get no transition.
+fn neighbour_levels(tiles: &Grid<TileType>, x: u32, y: u32) -> [u8; 8] {
+    let own = level(*tiles.get(x, y));
+    NEIGHBOURS.map(|(dx, dy)| {
+        let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+        if nx < 0 || ny < 0 || nx >= tiles.width as i64 || ny >= tiles.height as i64 {
+            own
+        } else {
+            level(*tiles.get(nx as u32, ny as u32))
+        }
+    })
+}
+
+/// Bit `i` is set when neighbour `i` passes `compare` against the tile's level.
+fn neighbour_mask(neighbours: &[u8; 8], compare: impl Fn(u8) -> bool) -> u8 {
+    neighbours
+        .iter()
+        .enumerate()
+        .filter(|(_, neighbour)| compare(**neighbour))
+        .fold(0, |mask, (bit, _)| mask | 1 << bit)
+}
+
+/// Synthesises the per-pixel levels of a tile from its own level and
+/// neighbours: straight edges, rounded outer corners and notched inner
+/// corners, with a beach fringe where land drops straight to water.
+fn synthesize(own: u8, neighbours: &[u8; 8], tile_size: u32) -> Vec<u8> {
+    let lower_mask = neighbour_mask(neighbours, |neighbour| neighbour < own);
+    let higher_mask = neighbour_mask(neighbours, |neighbour| neighbour > own);
+    let lower = |bit: usize| lower_mask & (1 << bit) != 0;
+    let higher = |bit: usize| higher_mask & (1 << bit) != 0;
+    let mut levels = Vec::with_capacity((tile_size * tile_size) as usize);
+
+    for pixel_y in 0..tile_size {
+        for pixel_x in 0..tile_size {
+            let u = (pixel_x as f32 + 0.5) / tile_size as f32;
+            let v = (pixel_y as f32 + 0.5) / tile_size as f32;
+            // Each lower neighbour contributes how far the pixel is inside
+            // the tile's own area; the nearest boundary decides the pixel.
+            let mut depth = f32::MAX;
+            let mut below = own;
+            let mut consider = |distance: f32, neighbour_level: u8| {
+                if distance < depth {
+                    depth = distance;
+                    below = neighbour_level;
+                }
+            };
+            // Where two higher sides meet, the corner beyond the arc is
+            // filled in from them, mirroring the rounding on their side.
+            let mut raised = None;
+
+            // Sides: distance from the N, E, S and W edges.
+            for (bit, distance) in [(0, v), (2, 1.0 - u), (4, 1.0 - v), (6, u)] {
+                if lower(bit) {
+                    consider(distance, neighbours[bit]);
+                }
+            }
+            // Corners, each with its diagonal and the two sides around it.
+            for (diagonal, side_a, side_b, corner_u, corner_v) in [
+                (1, 0, 2, 1.0, 0.0),
+                (3, 2, 4, 1.0, 1.0),
+                (5, 4, 6, 0.0, 1.0),
+                (7, 6, 0, 0.0, 0.0),
+            ] {
+                let (to_corner_u, to_corner_v) = ((u - corner_u).abs(), (v - corner_v).abs());
+                let in_corner = to_corner_u < CORNER_RADIUS && to_corner_v < CORNER_RADIUS;
+                let beyond_arc = ((CORNER_RADIUS - to_corner_u).powi(2)
+                    + (CORNER_RADIUS - to_corner_v).powi(2))
+                .sqrt()
+                    - CORNER_RADIUS;
+                if lower(side_a) && lower(side_b) {
+                    if in_corner {
+                        consider(-beyond_arc, neighbours[side_a].max(neighbours[side_b]));
+                    }
+                } else if higher(side_a) && higher(side_b) {
+                    if in_corner && beyond_arc > 0.0 {
+                        let level = neighbours[side_a].min(neighbours[side_b]);
+                        raised = Some(if level == LAND && own == WATER && beyond_arc < FRINGE {
+                            SHORE
+                        } else {
+                            level
+                        });
+                    }
+                } else if lower(diagonal) && !lower(side_a) && !lower(side_b) {
+                    let from_corner = (to_corner_u.powi(2) + to_corner_v.powi(2)).sqrt();
+                    consider(from_corner - NOTCH_RADIUS, neighbours[diagonal]);
+                }
+            }
+
+            levels.push(if depth < 0.0 {
+                below
+            } else if let Some(level) = raised {
+                level
+            } else if depth < FRINGE && own == LAND && below == WATER {
+                SHORE
+            } else {
+                own
+            });
+        }
+    }
+    levels
+}
+
+/// Pulls every pixel's height into the band of its synthesised level, so
+/// the elevation colours show exactly the classified coastline.
+pub fn autotile(tiles: &Grid<TileType>, heightmap: &mut Grid<f32>, tile_size: u32) {
+    // Only a handful of neighbourhoods occur, so each is synthesised once.
+    let mut synthesized: HashMap<(u8, [u8; 8]), Vec<u8>> = HashMap::new();
+
+    for tile_y in 0..tiles.height {
+        for tile_x in 0..tiles.width {
+            let own = level(*tiles.get(tile_x, tile_y));
+            let neighbours = neighbour_levels(tiles, tile_x, tile_y);
+            let levels = synthesized
+                .entry((own, neighbours))
+                .or_insert_with(|| synthesize(own, &neighbours, tile_size));
+
+            for (index, pixel_level) in levels.iter().enumerate() {
+                let pixel_x = tile_x * tile_size + index as u32 % tile_size;
+                let pixel_y = tile_y * tile_size + index as u32 / tile_size;
+                let height = *heightmap.get(pixel_x, pixel_y);
+                let band = match *pixel_level {
+                    WATER => TileType::Ocean,
+                    SHORE => TileType::Shore,
+                    _ => TileType::Land,
+                };
+                heightmap.set(pixel_x, pixel_y, clamp_to_band(band, height));
+            }
+        }
+    }
+}
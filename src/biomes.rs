@@ -1,3 +1,6 @@
+use crate::map_generator::TileType;
+use crate::noise_layer::{Fractal, NoiseSettings, NoiseSource};
+
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -5,97 +8,604 @@ pub struct Color {
     pub a: u8,
 }
 
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 255 }
+    }
+
+    pub fn to_array(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn lerp(&self, other: &Color, t: f64) -> [u8; 4] {
+        let mix = |a: u8, b: u8| (a as f64 * (1.0 - t) + b as f64 * t) as u8;
+        [
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.a, other.a),
+        ]
+    }
+}
+
+/// A gradient stop: terrain at exactly `height` takes `color`, and heights
+/// between two stops interpolate between their colours.
+pub struct ElevationBand {
+    pub height: f64,
+    pub color: Color,
+}
+
+/// Raw terrain-noise heights at which this biome's land meets the shore and
+/// the shore meets the sea; together they set how wet its maps are.
+#[derive(Clone, Copy)]
+pub struct Thresholds {
+    pub sea: f64,
+    pub land: f64,
+}
+
+/// How one kind of nature is scattered: features are never closer than
+/// `spacing` tiles, and `density` is the share of possible spots filled.
+#[derive(Clone, Copy)]
+pub struct Scatter {
+    pub spacing: f32,
+    pub density: f32,
+}
+
+/// Where in the biome's nature density field a spawn table places features.
+#[derive(Clone, Copy)]
+pub enum Cover {
+    /// Crowded where the field is high, like forests.
+    Dense,
+    /// Where the field is low, in the clearings between dense cover.
+    Sparse,
+    /// Evenly, ignoring the field.
+    Even,
+    /// Every free tile in the densest part of the field, ignoring the
+    /// spacing, so features join up into contiguous stands like forests.
+    Core,
+}
+
+/// One sprite a spawn table can pick, drawn in proportion to `weight`.
+pub struct SpawnEntry {
+    /// File name inside the biome's asset folder.
+    pub asset: &'static str,
+    pub weight: f32,
+    /// Most copies placed on one map, if limited.
+    pub max_count: Option<u32>,
+    /// Side of the square footprint, in tiles, that the feature covers.
+    pub size: u32,
+    /// Whether the sprite may be drawn slightly rotated, which suits rocks
+    /// but not upright sprites like trees.
+    pub rotates: bool,
+}
+
+impl SpawnEntry {
+    pub const fn new(asset: &'static str, weight: f32) -> SpawnEntry {
+        SpawnEntry {
+            asset,
+            weight,
+            max_count: None,
+            size: 1,
+            rotates: false,
+        }
+    }
+
+    /// Like `new`, for rocks and other sprites that read well at any angle.
+    pub const fn rock(asset: &'static str, weight: f32) -> SpawnEntry {
+        SpawnEntry {
+            rotates: true,
+            ..SpawnEntry::new(asset, weight)
+        }
+    }
+}
+
+/// Nature scattered over one tile type of a biome.
+pub struct SpawnTable {
+    pub tile: TileType,
+    pub scatter: Scatter,
+    pub cover: Cover,
+    pub entries: &'static [SpawnEntry],
+}
+
 pub struct Biome {
     pub asset_path: &'static str,
-    pub sea_color: Color,
-    pub shore_color: Color,
-    pub land_color: Color,
+    pub thresholds: Thresholds,
+    /// Ordered from lowest to highest.
+    pub bands: &'static [ElevationBand],
+    pub river_color: Color,
+    /// Mixed into mountain and cliff tiles so impassable ground stands out.
+    pub mountain_color: Color,
+    pub cliff_color: Color,
+    /// Blended over the terrain where roads run.
+    pub road_color: Color,
+    pub terrain_noise: NoiseSettings,
+    /// Density field read by the spawn tables' `Cover`.
+    pub nature_noise: NoiseSettings,
+    /// Applied in order, so earlier tables claim tiles first.
+    pub nature: &'static [SpawnTable],
 }
 
+const NATURE_NOISE: NoiseSettings = NoiseSettings {
+    source: NoiseSource::Perlin,
+    fractal: Fractal::Single,
+    octaves: 1,
+    lacunarity: 2.0,
+    persistence: 0.5,
+    frequency: 0.1,
+    offset: [0.0, 0.0],
+};
+
 pub const ALPINE: Biome = Biome {
     asset_path: "alpine/",
-    sea_color: Color {
-        r: 120,
-        g: 150,
-        b: 220,
-        a: 255,
+    thresholds: Thresholds {
+        sea: -0.5,
+        land: -0.38,
     },
-    shore_color: Color {
-        r: 160,
-        g: 130,
-        b: 90,
-        a: 255,
-    },
-    land_color: Color {
-        r: 40,
-        g: 80,
-        b: 50,
-        a: 255,
+    bands: &[
+        // deep water
+        ElevationBand {
+            height: -0.8,
+            color: Color::rgb(70, 100, 180),
+        },
+        // shallows
+        ElevationBand {
+            height: -0.5,
+            color: Color::rgb(120, 150, 220),
+        },
+        // beach
+        ElevationBand {
+            height: -0.4,
+            color: Color::rgb(160, 130, 90),
+        },
+        // lowland
+        ElevationBand {
+            height: -0.38,
+            color: Color::rgb(40, 80, 50),
+        },
+        // lowland
+        ElevationBand {
+            height: 0.15,
+            color: Color::rgb(40, 80, 50),
+        },
+        // hills
+        ElevationBand {
+            height: 0.35,
+            color: Color::rgb(70, 95, 55),
+        },
+        // mountain
+        ElevationBand {
+            height: 0.55,
+            color: Color::rgb(115, 110, 100),
+        },
+        // snowcap
+        ElevationBand {
+            height: 0.7,
+            color: Color::rgb(240, 240, 245),
+        },
+    ],
+    river_color: Color::rgb(90, 140, 210),
+    mountain_color: Color::rgb(95, 90, 85),
+    cliff_color: Color::rgb(60, 55, 50),
+    road_color: Color::rgb(135, 110, 80),
+    terrain_noise: NoiseSettings {
+        source: NoiseSource::Perlin,
+        fractal: Fractal::Fbm,
+        octaves: 6,
+        lacunarity: 2.0,
+        persistence: 0.5,
+        frequency: 0.0008,
+        offset: [0.0, 0.0],
     },
+    nature_noise: NATURE_NOISE,
+    nature: &[
+        // old-growth forest
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 1.0,
+                density: 1.0,
+            },
+            cover: Cover::Core,
+            entries: &[
+                SpawnEntry::new("tree1.png", 1.0),
+                SpawnEntry::new("tree2.png", 1.0),
+            ],
+        },
+        // pine forest
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 1.2,
+                density: 0.9,
+            },
+            cover: Cover::Dense,
+            entries: &[
+                SpawnEntry::new("tree1.png", 3.0),
+                SpawnEntry::new("tree2.png", 3.0),
+                SpawnEntry::new("tree3.png", 2.0),
+            ],
+        },
+        // meadow boulders
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 4.0,
+                density: 0.3,
+            },
+            cover: Cover::Sparse,
+            entries: &[
+                SpawnEntry::rock("rock1.png", 2.0),
+                SpawnEntry::rock("rock2.png", 2.0),
+                SpawnEntry::rock("rock3.png", 1.0),
+                SpawnEntry {
+                    size: 2,
+                    ..SpawnEntry::rock("rock3.png", 1.0)
+                },
+                SpawnEntry {
+                    max_count: Some(4),
+                    size: 3,
+                    ..SpawnEntry::rock("rock1.png", 0.5)
+                },
+            ],
+        },
+        // beach pebbles
+        SpawnTable {
+            tile: TileType::Shore,
+            scatter: Scatter {
+                spacing: 3.0,
+                density: 0.15,
+            },
+            cover: Cover::Even,
+            entries: &[
+                SpawnEntry::rock("rock2.png", 1.0),
+                SpawnEntry::rock("rock3.png", 1.0),
+            ],
+        },
+    ],
 };
 
 pub const DESERT: Biome = Biome {
     asset_path: "desert/",
-    sea_color: Color {
-        r: 120,
-        g: 150,
-        b: 220,
-        a: 255,
-    },
-    shore_color: Color {
-        r: 130,
-        g: 100,
-        b: 60,
-        a: 255,
+    thresholds: Thresholds {
+        sea: -0.55,
+        land: -0.47,
     },
-    land_color: Color {
-        r: 160,
-        g: 130,
-        b: 90,
-        a: 255,
+    bands: &[
+        // deep water
+        ElevationBand {
+            height: -0.8,
+            color: Color::rgb(80, 110, 190),
+        },
+        // shallows
+        ElevationBand {
+            height: -0.5,
+            color: Color::rgb(120, 150, 220),
+        },
+        // wet sand
+        ElevationBand {
+            height: -0.4,
+            color: Color::rgb(130, 100, 60),
+        },
+        // sand
+        ElevationBand {
+            height: -0.38,
+            color: Color::rgb(160, 130, 90),
+        },
+        // dunes
+        ElevationBand {
+            height: 0.2,
+            color: Color::rgb(175, 145, 100),
+        },
+        // mesa
+        ElevationBand {
+            height: 0.45,
+            color: Color::rgb(150, 110, 70),
+        },
+        // bare rock
+        ElevationBand {
+            height: 0.65,
+            color: Color::rgb(120, 85, 60),
+        },
+    ],
+    river_color: Color::rgb(100, 150, 200),
+    mountain_color: Color::rgb(140, 95, 65),
+    cliff_color: Color::rgb(95, 60, 40),
+    road_color: Color::rgb(160, 125, 85),
+    terrain_noise: NoiseSettings {
+        source: NoiseSource::OpenSimplex,
+        fractal: Fractal::Fbm,
+        octaves: 3,
+        lacunarity: 2.0,
+        persistence: 0.35,
+        frequency: 0.0008,
+        offset: [0.0, 0.0],
     },
+    nature_noise: NATURE_NOISE,
+    nature: &[
+        // oasis scrub
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 2.0,
+                density: 0.5,
+            },
+            cover: Cover::Dense,
+            entries: &[
+                SpawnEntry::new("tree1.png", 1.0),
+                SpawnEntry::new("tree2.png", 1.0),
+            ],
+        },
+        // cacti and stones on open sand
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 3.0,
+                density: 0.35,
+            },
+            cover: Cover::Sparse,
+            entries: &[
+                SpawnEntry::new("cactus.png", 3.0),
+                SpawnEntry::rock("rock1.png", 1.0),
+                SpawnEntry::rock("rock2.png", 1.0),
+                SpawnEntry::rock("rock3.png", 1.0),
+                SpawnEntry {
+                    max_count: Some(6),
+                    size: 3,
+                    ..SpawnEntry::rock("rock2.png", 1.0)
+                },
+            ],
+        },
+        // palms and reeds along the water
+        SpawnTable {
+            tile: TileType::Shore,
+            scatter: Scatter {
+                spacing: 1.5,
+                density: 0.4,
+            },
+            cover: Cover::Even,
+            entries: &[
+                SpawnEntry::new("palm.png", 1.0),
+                SpawnEntry::new("reeds.png", 2.0),
+            ],
+        },
+    ],
 };
 
 pub const TUNDRA: Biome = Biome {
     asset_path: "tundra/",
-    sea_color: Color {
-        r: 120,
-        g: 150,
-        b: 220,
-        a: 255,
+    thresholds: Thresholds {
+        sea: -0.4,
+        land: -0.3,
     },
-    shore_color: Color {
-        r: 140,
-        g: 145,
-        b: 150,
-        a: 255,
-    },
-    land_color: Color {
-        r: 248,
-        g: 248,
-        b: 255,
-        a: 255,
+    bands: &[
+        // deep water
+        ElevationBand {
+            height: -0.8,
+            color: Color::rgb(80, 105, 170),
+        },
+        // shallows
+        ElevationBand {
+            height: -0.5,
+            color: Color::rgb(120, 150, 220),
+        },
+        // shingle
+        ElevationBand {
+            height: -0.4,
+            color: Color::rgb(140, 145, 150),
+        },
+        // snowfield
+        ElevationBand {
+            height: -0.38,
+            color: Color::rgb(248, 248, 255),
+        },
+        // snowfield
+        ElevationBand {
+            height: 0.3,
+            color: Color::rgb(235, 238, 245),
+        },
+        // exposed rock
+        ElevationBand {
+            height: 0.5,
+            color: Color::rgb(150, 155, 165),
+        },
+        // ice cap
+        ElevationBand {
+            height: 0.7,
+            color: Color::rgb(255, 255, 255),
+        },
+    ],
+    river_color: Color::rgb(170, 200, 230),
+    mountain_color: Color::rgb(120, 125, 135),
+    cliff_color: Color::rgb(70, 75, 85),
+    road_color: Color::rgb(115, 105, 95),
+    terrain_noise: NoiseSettings {
+        source: NoiseSource::Combined(&[NoiseSource::Perlin, NoiseSource::Value]),
+        fractal: Fractal::Fbm,
+        octaves: 5,
+        lacunarity: 2.2,
+        persistence: 0.45,
+        frequency: 0.0008,
+        offset: [0.0, 0.0],
     },
+    nature_noise: NATURE_NOISE,
+    nature: &[
+        // taiga
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 1.8,
+                density: 0.6,
+            },
+            cover: Cover::Dense,
+            entries: &[
+                SpawnEntry::new("tree1.png", 2.0),
+                SpawnEntry::new("tree2.png", 2.0),
+                SpawnEntry::new("tree3.png", 1.0),
+            ],
+        },
+        // erratics
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 3.0,
+                density: 0.35,
+            },
+            cover: Cover::Sparse,
+            entries: &[
+                SpawnEntry::rock("rock1.png", 1.0),
+                SpawnEntry::rock("rock2.png", 1.0),
+                SpawnEntry::rock("rock3.png", 1.0),
+                SpawnEntry {
+                    size: 2,
+                    ..SpawnEntry::rock("rock2.png", 1.0)
+                },
+            ],
+        },
+        // shingle
+        SpawnTable {
+            tile: TileType::Shore,
+            scatter: Scatter {
+                spacing: 2.5,
+                density: 0.2,
+            },
+            cover: Cover::Even,
+            entries: &[SpawnEntry::rock("rock1.png", 1.0)],
+        },
+    ],
 };
 
 pub const ALIEN: Biome = Biome {
     asset_path: "alien/",
-    sea_color: Color {
-        r: 200,
-        g: 50,
-        b: 30,
-        a: 255,
+    thresholds: Thresholds {
+        sea: -0.7,
+        land: -0.6,
     },
-    shore_color: Color {
-        r: 65,
-        g: 70,
-        b: 75,
-        a: 255,
+    bands: &[
+        // deep lava
+        ElevationBand {
+            height: -0.8,
+            color: Color::rgb(140, 20, 10),
+        },
+        // lava
+        ElevationBand {
+            height: -0.5,
+            color: Color::rgb(200, 50, 30),
+        },
+        // ash
+        ElevationBand {
+            height: -0.4,
+            color: Color::rgb(65, 70, 75),
+        },
+        // crust
+        ElevationBand {
+            height: -0.38,
+            color: Color::rgb(25, 15, 35),
+        },
+        // crust
+        ElevationBand {
+            height: 0.25,
+            color: Color::rgb(45, 20, 60),
+        },
+        // ridges
+        ElevationBand {
+            height: 0.5,
+            color: Color::rgb(80, 40, 95),
+        },
+        // peaks
+        ElevationBand {
+            height: 0.7,
+            color: Color::rgb(140, 90, 160),
+        },
+    ],
+    river_color: Color::rgb(230, 90, 40),
+    mountain_color: Color::rgb(110, 60, 130),
+    cliff_color: Color::rgb(30, 10, 40),
+    road_color: Color::rgb(60, 45, 70),
+    terrain_noise: NoiseSettings {
+        source: NoiseSource::Perlin,
+        fractal: Fractal::RidgedMulti,
+        octaves: 4,
+        lacunarity: 2.0,
+        persistence: 0.6,
+        frequency: 0.0006,
+        offset: [0.0, 0.0],
     },
-    land_color: Color {
-        r: 25,
-        g: 15,
-        b: 35,
-        a: 255,
+    nature_noise: NoiseSettings {
+        source: NoiseSource::Worley,
+        ..NATURE_NOISE
     },
-};
\ No newline at end of file
+    nature: &[
+        // crystal groves
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 1.5,
+                density: 0.7,
+            },
+            cover: Cover::Dense,
+            entries: &[
+                SpawnEntry::new("tree1.png", 2.0),
+                SpawnEntry::new("tree2.png", 2.0),
+                SpawnEntry::new("crystal.png", 1.0),
+            ],
+        },
+        // boulders
+        SpawnTable {
+            tile: TileType::Land,
+            scatter: Scatter {
+                spacing: 2.5,
+                density: 0.4,
+            },
+            cover: Cover::Sparse,
+            entries: &[
+                SpawnEntry::rock("rock1.png", 1.0),
+                SpawnEntry::rock("rock2.png", 1.0),
+                SpawnEntry::rock("rock3.png", 1.0),
+                SpawnEntry {
+                    size: 2,
+                    ..SpawnEntry::rock("rock1.png", 1.0)
+                },
+            ],
+        },
+        // crystal shards cooling on the lava shore, kept rare
+        SpawnTable {
+            tile: TileType::Shore,
+            scatter: Scatter {
+                spacing: 4.0,
+                density: 0.3,
+            },
+            cover: Cover::Even,
+            entries: &[SpawnEntry {
+                max_count: Some(12),
+                ..SpawnEntry::new("crystal.png", 1.0)
+            }],
+        },
+    ],
+};
+
+pub const BIOMES: [&Biome; 4] = [&ALPINE, &DESERT, &TUNDRA, &ALIEN];
+
+/// Position of `biome` in `BIOMES`, used to index per-biome tables.
+pub fn biome_index(biome: &Biome) -> usize {
+    BIOMES
+        .iter()
+        .position(|candidate| candidate.asset_path == biome.asset_path)
+        .expect("every biome is listed in BIOMES")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn every_spawn_entry_has_its_sprite() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        for biome in BIOMES {
+            for entry in biome.nature.iter().flat_map(|table| table.entries) {
+                let path = assets.join(biome.asset_path).join(entry.asset);
+                assert!(path.is_file(), "missing sprite {}", path.display());
+            }
+        }
+    }
+}
@@ -1,16 +1,18 @@
 use bevy::prelude::*;
 use bevy::app::AppExit;
 
+use crate::map_generator::MapConfig;
 use crate::tank::Unit;
 
-const TILE_SIZE: f32 = 32.0;
-const WIDTH: f32 = 100.0;
-const HEIGHT: f32 = 100.0;
 const BORDER: f32 = 10.0;
 
-pub fn setup_camera(mut commands: Commands) {
+pub fn setup_camera(mut commands: Commands, config: Res<MapConfig>) {
     commands.spawn(Camera2dBundle {
-        transform: Transform::from_translation(Vec3::new(1600.0, 1600.0, 0.0)),
+        transform: Transform::from_translation(Vec3::new(
+            config.world_width() / 2.0,
+            config.world_height() / 2.0,
+            0.0,
+        )),
         ..default()
     });
 }
@@ -37,6 +39,7 @@ pub fn camera_controls(
     time: Res<Time>,
     windows: Query<&Window>,
     mut exit: EventWriter<AppExit>,
+    config: Res<MapConfig>,
 ) {
     let mut camera_transform = camera_query.single_mut();
     let speed = 500.0;
@@ -44,13 +47,14 @@ pub fn camera_controls(
     let window_center_width = window.width() / 2.0;
     let window_center_height = window.height() / 2.0;
     let edge_threshold = 50.0;
-    let map_bound_x: f32 = WIDTH * TILE_SIZE;
-    let map_bound_y: f32 = HEIGHT * TILE_SIZE;
+    let tile_size = config.tile_size as f32;
+    let map_bound_x: f32 = config.world_width();
+    let map_bound_y: f32 = config.world_height();
 
-    let max_x = map_bound_x - window_center_width + TILE_SIZE + BORDER;
-    let min_x = window_center_width - TILE_SIZE - BORDER;
-    let max_y = map_bound_y - window_center_height + TILE_SIZE + BORDER;
-    let min_y = window_center_height - TILE_SIZE - BORDER;
+    let max_x = map_bound_x - window_center_width + tile_size + BORDER;
+    let min_x = window_center_width - tile_size - BORDER;
+    let max_y = map_bound_y - window_center_height + tile_size + BORDER;
+    let min_y = window_center_height - tile_size - BORDER;
     
     // Exit game
     if keyboard_input.just_pressed(KeyCode::Escape) {
@@ -87,6 +91,15 @@ pub fn camera_controls(
         }
     }
     
-    camera_transform.translation.x = camera_transform.translation.x.clamp(min_x, max_x);
-    camera_transform.translation.y = camera_transform.translation.y.clamp(min_y, max_y);
+    camera_transform.translation.x = clamp_or_center(camera_transform.translation.x, min_x, max_x);
+    camera_transform.translation.y = clamp_or_center(camera_transform.translation.y, min_y, max_y);
+}
+
+// Maps smaller than the window have no valid scroll range, so keep them centred.
+fn clamp_or_center(value: f32, min: f32, max: f32) -> f32 {
+    if min <= max {
+        value.clamp(min, max)
+    } else {
+        (min + max) / 2.0
+    }
 }
\ No newline at end of file
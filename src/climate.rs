@@ -0,0 +1,123 @@
+//! Moisture and temperature fields, and the Whittaker-style lookup that turns
+//! them into a biome per tile.
+
+use crate::biomes::*;
+use crate::grid::Grid;
+use crate::noise_layer::{Fractal, NoiseLayer, NoiseSettings, NoiseSource};
+use rand::Rng;
+
+#[derive(Clone, Copy)]
+pub struct ClimateSettings {
+    pub moisture_noise: NoiseSettings,
+    /// Optional temperature variation on top of the north-south gradient.
+    pub temperature_noise: Option<NoiseSettings>,
+    /// Temperature difference between the south (warm) and north (cold) edges.
+    pub latitude_gradient: f64,
+    /// Temperature lost per unit of elevation above sea level.
+    pub lapse_rate: f64,
+    /// Width, in tiles, over which neighbouring biomes blend into each other.
+    pub transition_width: u32,
+}
+
+impl Default for ClimateSettings {
+    fn default() -> Self {
+        ClimateSettings {
+            moisture_noise: NoiseSettings {
+                source: NoiseSource::Perlin,
+                fractal: Fractal::Fbm,
+                octaves: 4,
+                lacunarity: 2.0,
+                persistence: 0.5,
+                frequency: 0.0005,
+                offset: [0.0, 0.0],
+            },
+            temperature_noise: Some(NoiseSettings {
+                source: NoiseSource::Perlin,
+                fractal: Fractal::Fbm,
+                octaves: 3,
+                lacunarity: 2.0,
+                persistence: 0.5,
+                frequency: 0.0004,
+                offset: [0.0, 0.0],
+            }),
+            latitude_gradient: 1.6,
+            lapse_rate: 0.8,
+            transition_width: 4,
+        }
+    }
+}
+
+pub struct ClimateLayer {
+    moisture: NoiseLayer,
+    temperature: Option<NoiseLayer>,
+    latitude_gradient: f64,
+    lapse_rate: f64,
+}
+
+impl ClimateLayer {
+    pub fn new(settings: &ClimateSettings, rng: &mut impl Rng) -> Self {
+        ClimateLayer {
+            moisture: NoiseLayer::new(&settings.moisture_noise, rng.gen()),
+            temperature: settings
+                .temperature_noise
+                .map(|noise| NoiseLayer::new(&noise, rng.gen())),
+            latitude_gradient: settings.latitude_gradient,
+            lapse_rate: settings.lapse_rate,
+        }
+    }
+
+    /// Picks the biome at pixel (`x`, `y`), where `latitude` runs from the
+    /// north edge (0) to the south edge (1).
+    pub fn biome_at(&self, x: f64, y: f64, latitude: f64, elevation: f64) -> &'static Biome {
+        let moisture = self.moisture.get(x, y);
+        let mut temperature = (latitude - 0.5) * self.latitude_gradient;
+        if let Some(noise) = &self.temperature {
+            temperature += noise.get(x, y) * 0.5;
+        }
+        temperature -= elevation.max(0.0) * self.lapse_rate;
+
+        whittaker_biome(elevation, moisture, temperature)
+    }
+}
+
+/// Cold ground is tundra, high ground is alpine and dry lowland is desert;
+/// everything else falls back to temperate alpine forest.
+fn whittaker_biome(elevation: f64, moisture: f64, temperature: f64) -> &'static Biome {
+    match (elevation, moisture, temperature) {
+        (_, _, t) if t < -0.45 => &TUNDRA,
+        (e, _, _) if e > 0.4 => &ALPINE,
+        (_, m, t) if m < -0.1 && t > -0.2 => &DESERT,
+        _ => &ALPINE,
+    }
+}
+
+/// Share of each entry in `BIOMES` at a tile; the entries sum to 1.
+pub type BiomeWeights = [f32; BIOMES.len()];
+
+/// Box-blurs the per-tile biomes over `transition_width` tiles so that
+/// borders fade from one biome to the next instead of switching abruptly.
+pub fn biome_weights(biomes: &Grid<&'static Biome>, transition_width: u32) -> Grid<BiomeWeights> {
+    let radius = (transition_width / 2) as i64;
+    let (width, height) = (biomes.width as i64, biomes.height as i64);
+
+    let horizontal = Grid::from_fn(biomes.width, biomes.height, |x, y| {
+        let mut weights = [0.0; BIOMES.len()];
+        for dx in -radius..=radius {
+            let sample_x = (x as i64 + dx).clamp(0, width - 1) as u32;
+            weights[biome_index(biomes.get(sample_x, y))] += 1.0;
+        }
+        weights
+    });
+
+    Grid::from_fn(biomes.width, biomes.height, |x, y| {
+        let mut weights = [0.0; BIOMES.len()];
+        for dy in -radius..=radius {
+            let sample_y = (y as i64 + dy).clamp(0, height - 1) as u32;
+            for (total, weight) in weights.iter_mut().zip(horizontal.get(x, sample_y)) {
+                *total += weight;
+            }
+        }
+        let sum: f32 = weights.iter().sum();
+        weights.map(|weight| weight / sum)
+    })
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use crate::map_generator::MapConfig;
 use crate::tank::{Unit, Selectable, Selected, HealthBar};
 
 #[derive(Resource, Default)]
@@ -8,8 +9,6 @@ pub struct DragSelection {
     pub current_pos: Vec2,
 }
 
-const TILE_SIZE: u32 = 32;
-
 pub fn handle_unit_selection(
     mut commands: Commands,
     mouse_input: Res<Input<MouseButton>>,
@@ -17,6 +16,7 @@ pub fn handle_unit_selection(
     camera_query: Query<(&Camera, &GlobalTransform)>,
     selectable_query: Query<(Entity, &Transform), With<Selectable>>,
     selected_query: Query<Entity, With<Selected>>,
+    config: Res<MapConfig>,
 ) {
     if mouse_input.just_pressed(MouseButton::Left) {
         let window = windows.single();
@@ -29,7 +29,7 @@ pub fn handle_unit_selection(
                     let tank_pos = transform.translation.truncate();
                     let distance = (world_pos - tank_pos).length();
                     
-                    if distance < TILE_SIZE as f32 / 2.0 {
+                    if distance < config.tile_size as f32 / 2.0 {
                         // Deselect all first, then select this one
                         for selected_entity in selected_query.iter() {
                             commands.entity(selected_entity).remove::<Selected>();
@@ -125,6 +125,7 @@ pub fn draw_selection_ui(
     mut gizmos: Gizmos,
     selected_tanks: Query<(&Transform, &Unit), With<Selected>>,
     health_bars: Query<Entity, With<HealthBar>>,
+    config: Res<MapConfig>,
 ) {
     // Remove existing health bars
     for entity in health_bars.iter() {
@@ -133,7 +134,7 @@ pub fn draw_selection_ui(
     
     for (transform, unit) in selected_tanks.iter() {
         let pos = transform.translation.truncate();
-        let size = TILE_SIZE as f32;
+        let size = config.tile_size as f32;
         
         draw_selection_corners(&mut gizmos, pos, size);
         
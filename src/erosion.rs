@@ -0,0 +1,232 @@
+//! Hydraulic (droplet) and thermal erosion of the terrain heightmap.
+//!
+//! The simulation runs on a coarser grid of `cell_size` pixel cells, which
+//! keeps it fast on full-resolution maps and carves features at the scale
+//! tiles are classified at. The height change is then interpolated back onto
+//! every pixel, so the noise's fine detail survives.
+
+use crate::grid::Grid;
+use rand::Rng;
+
+const INERTIA: f32 = 0.05;
+const MIN_CAPACITY: f32 = 0.01;
+const ERODE_SPEED: f32 = 0.3;
+const DEPOSIT_SPEED: f32 = 0.3;
+const EVAPORATE_SPEED: f32 = 0.02;
+const GRAVITY: f32 = 4.0;
+const MAX_LIFETIME: u32 = 40;
+const BRUSH_RADIUS: i32 = 2;
+
+#[derive(Clone, Copy)]
+pub struct ErosionSettings {
+    /// Raindrops simulated by the hydraulic pass.
+    pub droplets: u32,
+    /// Scales how much soil a droplet can carry, and so how deep valleys cut.
+    pub hydraulic_strength: f32,
+    /// Passes of the thermal pass, each letting over-steep slopes slump once.
+    pub thermal_iterations: u32,
+    /// Fraction of the excess over the talus slope moved per thermal pass.
+    pub thermal_strength: f32,
+    /// Steepest height difference between neighbouring cells that stays put.
+    pub talus: f32,
+    /// Pixels per side of a simulation cell.
+    pub cell_size: u32,
+}
+
+impl Default for ErosionSettings {
+    fn default() -> Self {
+        ErosionSettings {
+            droplets: 80_000,
+            hydraulic_strength: 4.0,
+            thermal_iterations: 20,
+            thermal_strength: 0.25,
+            talus: 0.02,
+            cell_size: 8,
+        }
+    }
+}
+
+/// Erodes `heightmap` in place.
+pub fn erode(heightmap: &mut Grid<f32>, settings: &ErosionSettings, rng: &mut impl Rng) {
+    let cell_size = settings.cell_size.max(1);
+    let width = heightmap.width.div_ceil(cell_size);
+    let height = heightmap.height.div_ceil(cell_size);
+    let original = Grid::from_fn(width, height, |x, y| cell_mean(heightmap, cell_size, x, y));
+    let mut cells = Grid::from_fn(width, height, |x, y| *original.get(x, y));
+
+    if width > 2 && height > 2 {
+        for _ in 0..settings.droplets {
+            simulate_droplet(&mut cells, settings.hydraulic_strength, rng);
+        }
+    }
+    for _ in 0..settings.thermal_iterations {
+        slump(&mut cells, settings);
+    }
+
+    for pixel_y in 0..heightmap.height {
+        for pixel_x in 0..heightmap.width {
+            let fx =
+                ((pixel_x as f32 + 0.5) / cell_size as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+            let fy =
+                ((pixel_y as f32 + 0.5) / cell_size as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+            let change = sample(&cells, fx, fy) - sample(&original, fx, fy);
+            let value = *heightmap.get(pixel_x, pixel_y) + change;
+            heightmap.set(pixel_x, pixel_y, value);
+        }
+    }
+}
+
+fn cell_mean(heightmap: &Grid<f32>, cell_size: u32, cell_x: u32, cell_y: u32) -> f32 {
+    let (mut sum, mut count) = (0.0, 0);
+    for pixel_y in cell_y * cell_size..((cell_y + 1) * cell_size).min(heightmap.height) {
+        for pixel_x in cell_x * cell_size..((cell_x + 1) * cell_size).min(heightmap.width) {
+            sum += *heightmap.get(pixel_x, pixel_y);
+            count += 1;
+        }
+    }
+    sum / count as f32
+}
+
+/// Bilinearly interpolated height at a fractional cell position.
+fn sample(cells: &Grid<f32>, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x as u32, y as u32);
+    let (x1, y1) = (
+        (x0 + 1).min(cells.width - 1),
+        (y0 + 1).min(cells.height - 1),
+    );
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let top = cells.get(x0, y0) * (1.0 - tx) + cells.get(x1, y0) * tx;
+    let bottom = cells.get(x0, y1) * (1.0 - tx) + cells.get(x1, y1) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+fn gradient(cells: &Grid<f32>, x: f32, y: f32) -> (f32, f32) {
+    let (x0, y0) = (x as u32, y as u32);
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let (nw, ne) = (*cells.get(x0, y0), *cells.get(x0 + 1, y0));
+    let (sw, se) = (*cells.get(x0, y0 + 1), *cells.get(x0 + 1, y0 + 1));
+    (
+        (ne - nw) * (1.0 - ty) + (se - sw) * ty,
+        (sw - nw) * (1.0 - tx) + (se - ne) * tx,
+    )
+}
+
+/// Rolls one raindrop downhill, picking up soil where it speeds up and
+/// dropping it where it slows or runs uphill.
+fn simulate_droplet(cells: &mut Grid<f32>, strength: f32, rng: &mut impl Rng) {
+    let max_x = (cells.width - 1) as f32;
+    let max_y = (cells.height - 1) as f32;
+    let (mut x, mut y) = (rng.gen_range(0.0..max_x), rng.gen_range(0.0..max_y));
+    let (mut direction_x, mut direction_y) = (0.0, 0.0);
+    let (mut speed, mut water, mut sediment) = (1.0f32, 1.0f32, 0.0f32);
+
+    for _ in 0..MAX_LIFETIME {
+        let height = sample(cells, x, y);
+        let (gradient_x, gradient_y) = gradient(cells, x, y);
+        direction_x = direction_x * INERTIA - gradient_x * (1.0 - INERTIA);
+        direction_y = direction_y * INERTIA - gradient_y * (1.0 - INERTIA);
+        let length = (direction_x * direction_x + direction_y * direction_y).sqrt();
+        if length <= f32::EPSILON {
+            break;
+        }
+        let (previous_x, previous_y) = (x, y);
+        x += direction_x / length;
+        y += direction_y / length;
+        if x < 0.0 || y < 0.0 || x >= max_x || y >= max_y {
+            break;
+        }
+
+        let height_change = sample(cells, x, y) - height;
+        let capacity = (-height_change * speed * water * strength).max(MIN_CAPACITY);
+        if height_change > 0.0 || sediment > capacity {
+            let deposit = if height_change > 0.0 {
+                height_change.min(sediment)
+            } else {
+                (sediment - capacity) * DEPOSIT_SPEED
+            };
+            sediment -= deposit;
+            deposit_at(cells, previous_x, previous_y, deposit);
+        } else {
+            let eroded = ((capacity - sediment) * ERODE_SPEED).min(-height_change);
+            sediment += erode_around(cells, previous_x, previous_y, eroded);
+        }
+
+        speed = (speed * speed - height_change * GRAVITY).max(0.0).sqrt();
+        water *= 1.0 - EVAPORATE_SPEED;
+    }
+}
+
+/// Spreads `amount` over the four cells around a fractional position.
+fn deposit_at(cells: &mut Grid<f32>, x: f32, y: f32, amount: f32) {
+    let (x0, y0) = (x as u32, y as u32);
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    for (cell_x, cell_y, share) in [
+        (x0, y0, (1.0 - tx) * (1.0 - ty)),
+        (x0 + 1, y0, tx * (1.0 - ty)),
+        (x0, y0 + 1, (1.0 - tx) * ty),
+        (x0 + 1, y0 + 1, tx * ty),
+    ] {
+        let value = *cells.get(cell_x, cell_y) + amount * share;
+        cells.set(cell_x, cell_y, value);
+    }
+}
+
+/// Removes up to `amount` from a disc of cells, weighted towards its centre
+/// so droplets carve smooth channels rather than pits. Returns what was taken.
+fn erode_around(cells: &mut Grid<f32>, x: f32, y: f32, amount: f32) -> f32 {
+    let mut brush = Vec::with_capacity(((BRUSH_RADIUS * 2 + 1) * (BRUSH_RADIUS * 2 + 1)) as usize);
+    let mut total_weight = 0.0;
+    for offset_y in -BRUSH_RADIUS..=BRUSH_RADIUS {
+        for offset_x in -BRUSH_RADIUS..=BRUSH_RADIUS {
+            let (cell_x, cell_y) = (x.round() as i32 + offset_x, y.round() as i32 + offset_y);
+            if cell_x < 0
+                || cell_y < 0
+                || cell_x >= cells.width as i32
+                || cell_y >= cells.height as i32
+            {
+                continue;
+            }
+            let distance = ((cell_x as f32 - x).powi(2) + (cell_y as f32 - y).powi(2)).sqrt();
+            let weight = (1.0 - distance / BRUSH_RADIUS as f32).max(0.0);
+            if weight > 0.0 {
+                brush.push((cell_x as u32, cell_y as u32, weight));
+                total_weight += weight;
+            }
+        }
+    }
+
+    let mut taken = 0.0;
+    for (cell_x, cell_y, weight) in brush {
+        let share = amount * weight / total_weight;
+        let value = *cells.get(cell_x, cell_y) - share;
+        cells.set(cell_x, cell_y, value);
+        taken += share;
+    }
+    taken
+}
+
+/// One thermal pass: material on slopes steeper than the talus slides to
+/// the lower neighbours.
+fn slump(cells: &mut Grid<f32>, settings: &ErosionSettings) {
+    let mut change = Grid::from_fn(cells.width, cells.height, |_, _| 0.0f32);
+    for y in 0..cells.height {
+        for x in 0..cells.width {
+            let height = *cells.get(x, y);
+            for (nx, ny) in cells.neighbours4(x, y) {
+                let excess = height - cells.get(nx, ny) - settings.talus;
+                if excess > 0.0 {
+                    // Each of up to four neighbours may take a share, so a
+                    // quarter keeps a cell from sliding below its neighbours.
+                    let moved = excess * settings.thermal_strength / 4.0;
+                    change.set(x, y, change.get(x, y) - moved);
+                    change.set(nx, ny, change.get(nx, ny) + moved);
+                }
+            }
+        }
+    }
+    for y in 0..cells.height {
+        for x in 0..cells.width {
+            cells.set(x, y, cells.get(x, y) + change.get(x, y));
+        }
+    }
+}
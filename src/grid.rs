@@ -0,0 +1,54 @@
+/// Row-major 2D storage used for every per-tile and per-pixel map layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    pub width: u32,
+    pub height: u32,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(u32, u32) -> T) -> Self {
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(x, y));
+            }
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> &T {
+        debug_assert!(x < self.width && y < self.height);
+        &self.cells[(y * self.width + x) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, value: T) {
+        debug_assert!(x < self.width && y < self.height);
+        self.cells[(y * self.width + x) as usize] = value;
+    }
+
+    /// The up to four cells sharing an edge with (`x`, `y`).
+    pub fn neighbours4(&self, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        [(0i64, -1i64), (-1, 0), (1, 0), (0, 1)]
+            .into_iter()
+            .map(move |(dx, dy)| (x as i64 + dx, y as i64 + dy))
+            .filter(move |&(nx, ny)| nx >= 0 && ny >= 0 && nx < width && ny < height)
+            .map(|(nx, ny)| (nx as u32, ny as u32))
+    }
+
+    /// The up to eight cells surrounding (`x`, `y`) that lie inside the grid.
+    pub fn neighbours8(&self, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        (-1i64..=1)
+            .flat_map(|dy| (-1i64..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .map(move |(dx, dy)| (x as i64 + dx, y as i64 + dy))
+            .filter(move |&(nx, ny)| nx >= 0 && ny >= 0 && nx < width && ny < height)
+            .map(|(nx, ny)| (nx as u32, ny as u32))
+    }
+}
@@ -0,0 +1,74 @@
+//! Relief shading of the terrain texture from the heightmap gradient.
+
+use crate::grid::Grid;
+use crate::map_generator::SEA_THRESHOLD;
+
+/// Darkest and brightest multipliers `shade` returns.
+const SHADE_RANGE: (f32, f32) = (0.5, 1.5);
+/// Steps a packed shade is quantised to, even so that flat ground packs exactly.
+const SHADE_STEPS: f32 = 254.0;
+
+#[derive(Clone, Copy)]
+pub struct Hillshade {
+    /// Direction the light comes from, in degrees clockwise from north (up).
+    pub azimuth: f32,
+    /// Height of the light above the horizon, in degrees.
+    pub altitude: f32,
+    /// Vertical exaggeration applied to the noise heights, which are tiny
+    /// compared with the pixel spacing.
+    pub exaggeration: f32,
+}
+
+impl Default for Hillshade {
+    fn default() -> Self {
+        Hillshade {
+            azimuth: 315.0,
+            altitude: 45.0,
+            exaggeration: 40.0,
+        }
+    }
+}
+
+impl Hillshade {
+    /// Brightness multiplier for a pixel: 1.0 on flat ground, brighter on
+    /// slopes facing the light and darker on those facing away. Water is
+    /// treated as flat at sea level.
+    pub fn shade(&self, heightmap: &Grid<f32>, x: u32, y: u32) -> f32 {
+        let height_at = |x: u32, y: u32| heightmap.get(x, y).max(SEA_THRESHOLD as f32);
+        let (left, right) = (x.saturating_sub(1), (x + 1).min(heightmap.width - 1));
+        let (up, down) = (y.saturating_sub(1), (y + 1).min(heightmap.height - 1));
+        let slope_x = (height_at(right, y) - height_at(left, y)) / (right - left).max(1) as f32;
+        let slope_y = (height_at(x, down) - height_at(x, up)) / (down - up).max(1) as f32;
+
+        // Surface normal, with y pointing down the texture (south).
+        let normal = [
+            -slope_x * self.exaggeration,
+            -slope_y * self.exaggeration,
+            1.0,
+        ];
+        let length = (normal[0] * normal[0] + normal[1] * normal[1] + 1.0).sqrt();
+
+        let (azimuth, altitude) = (self.azimuth.to_radians(), self.altitude.to_radians());
+        let light = [
+            azimuth.sin() * altitude.cos(),
+            -azimuth.cos() * altitude.cos(),
+            altitude.sin(),
+        ];
+        let lit = (normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2]) / length;
+        (lit / altitude.sin()).clamp(SHADE_RANGE.0, SHADE_RANGE.1)
+    }
+
+    /// Shades every pixel of `heightmap`, each packed into a byte so the
+    /// result takes a quarter of the heightmap's memory.
+    pub fn shade_map(&self, heightmap: &Grid<f32>) -> Grid<u8> {
+        Grid::from_fn(heightmap.width, heightmap.height, |x, y| {
+            let shade = self.shade(heightmap, x, y);
+            ((shade - SHADE_RANGE.0) / (SHADE_RANGE.1 - SHADE_RANGE.0) * SHADE_STEPS).round() as u8
+        })
+    }
+}
+
+/// The brightness multiplier a byte from `Hillshade::shade_map` stands for.
+pub fn unpack_shade(packed: u8) -> f32 {
+    SHADE_RANGE.0 + packed as f32 / SHADE_STEPS * (SHADE_RANGE.1 - SHADE_RANGE.0)
+}
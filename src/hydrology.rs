@@ -0,0 +1,193 @@
+//! Downhill water routing over the tile heightmap, used to carve rivers.
+
+use crate::grid::Grid;
+use crate::map_generator::TileType;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+#[derive(Clone, Copy)]
+pub struct RiverSettings {
+    /// Number of upstream tiles that must drain through a tile for it to become river.
+    pub min_flow: u32,
+    /// Widest river, as a fraction of the tile size.
+    pub max_width: f32,
+}
+
+pub struct Drainage {
+    /// The tile each tile drains into; `None` for outlets (sea, lakes and map edge).
+    pub downstream: Grid<Option<(u32, u32)>>,
+    /// Number of tiles, including itself, whose water passes through each tile.
+    pub flow: Grid<u32>,
+    /// Level water rises to before it can leave each tile; above the tile's
+    /// own elevation only in a depression, where it is the height of the rim.
+    pub spill: Grid<f32>,
+}
+
+struct FloodCell {
+    height: f32,
+    order: usize,
+    x: u32,
+    y: u32,
+}
+
+impl PartialEq for FloodCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FloodCell {}
+
+impl PartialOrd for FloodCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FloodCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.height
+            .total_cmp(&other.height)
+            .then(self.order.cmp(&other.order))
+    }
+}
+
+/// Routes water with a priority flood from open water and map edges: tiles are
+/// reached lowest-first, so each one drains towards the tile it was reached
+/// from and depressions spill over their lowest rim instead of trapping water.
+pub fn drain(elevations: &Grid<f32>, tiles: &Grid<TileType>) -> Drainage {
+    let (width, height) = (tiles.width, tiles.height);
+    let mut downstream = Grid::from_fn(width, height, |_, _| None);
+    let mut visited = Grid::from_fn(width, height, |_, _| false);
+    let mut spill = Grid::from_fn(width, height, |x, y| *elevations.get(x, y));
+    let mut queue = BinaryHeap::new();
+    let mut order = Vec::with_capacity((width * height) as usize);
+    let mut pushed = 0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let on_edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            if on_edge || tiles.get(x, y).is_water() {
+                visited.set(x, y, true);
+                queue.push(Reverse(FloodCell {
+                    height: *elevations.get(x, y),
+                    order: pushed,
+                    x,
+                    y,
+                }));
+                pushed += 1;
+            }
+        }
+    }
+
+    while let Some(Reverse(cell)) = queue.pop() {
+        order.push((cell.x, cell.y));
+        spill.set(cell.x, cell.y, cell.height);
+        for (nx, ny) in tiles.neighbours8(cell.x, cell.y) {
+            if *visited.get(nx, ny) {
+                continue;
+            }
+            visited.set(nx, ny, true);
+            downstream.set(nx, ny, Some((cell.x, cell.y)));
+            // A tile in a pit is raised to its spill height so the flood keeps
+            // working outwards from the rim rather than diving into the pit.
+            queue.push(Reverse(FloodCell {
+                height: elevations.get(nx, ny).max(cell.height),
+                order: pushed,
+                x: nx,
+                y: ny,
+            }));
+            pushed += 1;
+        }
+    }
+
+    // Every tile is popped after the tile it drains into, so walking the
+    // order backwards pushes each tile's flow downstream exactly once.
+    let mut flow = Grid::from_fn(width, height, |x, y| u32::from(!tiles.get(x, y).is_water()));
+    for &(x, y) in order.iter().rev() {
+        if let Some((dx, dy)) = *downstream.get(x, y) {
+            let total = *flow.get(dx, dy) + *flow.get(x, y);
+            flow.set(dx, dy, total);
+        }
+    }
+
+    Drainage {
+        downstream,
+        flow,
+        spill,
+    }
+}
+
+/// Paints each river tile as a capsule from its centre to the centre of the
+/// tile it drains into, widening with flow. Returns a per-pixel river mask.
+pub fn rasterize_rivers(
+    drainage: &Drainage,
+    tiles: &Grid<TileType>,
+    settings: &RiverSettings,
+    tile_size: u32,
+) -> Grid<bool> {
+    let mut pixels = Grid::from_fn(tiles.width * tile_size, tiles.height * tile_size, |_, _| {
+        false
+    });
+    let center = |x: u32, y: u32| {
+        (
+            (x * tile_size) as f32 + tile_size as f32 / 2.0,
+            (y * tile_size) as f32 + tile_size as f32 / 2.0,
+        )
+    };
+
+    for y in 0..tiles.height {
+        for x in 0..tiles.width {
+            if *tiles.get(x, y) != TileType::River {
+                continue;
+            }
+            let relative_flow = *drainage.flow.get(x, y) as f32 / settings.min_flow as f32;
+            let width =
+                (0.3 + 0.15 * relative_flow.log2()).min(settings.max_width) * tile_size as f32;
+            let start = center(x, y);
+            let end = match *drainage.downstream.get(x, y) {
+                Some((dx, dy)) => center(dx, dy),
+                None => start,
+            };
+            paint_capsule(&mut pixels, tiles, tile_size, start, end, width / 2.0);
+        }
+    }
+    pixels
+}
+
+fn paint_capsule(
+    pixels: &mut Grid<bool>,
+    tiles: &Grid<TileType>,
+    tile_size: u32,
+    start: (f32, f32),
+    end: (f32, f32),
+    radius: f32,
+) {
+    let min_x = (start.0.min(end.0) - radius).max(0.0) as u32;
+    let min_y = (start.1.min(end.1) - radius).max(0.0) as u32;
+    let max_x = ((start.0.max(end.0) + radius) as u32).min(pixels.width - 1);
+    let max_y = ((start.1.max(end.1) + radius) as u32).min(pixels.height - 1);
+    let (segment_x, segment_y) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = (segment_x * segment_x + segment_y * segment_y).max(f32::EPSILON);
+
+    for pixel_y in min_y..=max_y {
+        for pixel_x in min_x..=max_x {
+            // Leave the sea or lake itself untouched where a river runs into it.
+            if tiles
+                .get(pixel_x / tile_size, pixel_y / tile_size)
+                .is_water()
+            {
+                continue;
+            }
+            let (px, py) = (
+                pixel_x as f32 + 0.5 - start.0,
+                pixel_y as f32 + 0.5 - start.1,
+            );
+            let t = ((px * segment_x + py * segment_y) / length_squared).clamp(0.0, 1.0);
+            let (dx, dy) = (px - segment_x * t, py - segment_y * t);
+            if dx * dx + dy * dy <= radius * radius {
+                pixels.set(pixel_x, pixel_y, true);
+            }
+        }
+    }
+}
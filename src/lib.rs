@@ -0,0 +1,19 @@
+//! Map generation, usable without starting an `App`: `map_generator::MapData`
+//! builds a whole map from a seed and a `map_generator::MapConfig`, and
+//! `map_components` holds the ECS types the game spawns it as.
+
+pub mod autotile;
+pub mod biomes;
+pub mod climate;
+pub mod erosion;
+pub mod grid;
+pub mod hillshade;
+pub mod hydrology;
+pub mod map_components;
+pub mod map_generator;
+pub mod noise_layer;
+pub mod poisson;
+pub mod regions;
+pub mod roads;
+pub mod settlements;
+pub mod shape_mask;
@@ -1,22 +1,34 @@
 use bevy::prelude::*;
 
-mod biomes;
+use perlin_map_generator::{hillshade, map_components, map_generator, regions, settlements};
+
 mod camera;
 mod controls;
-mod map_components;
 mod map_renderer;
 mod movement;
 mod tank;
 
 fn main() {
+    let seed = map_components::MapSeed::from_args();
+    let config = map_generator::MapConfig::from_args();
+
     App::new()
-        .add_plugins(DefaultPlugins)
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: format!("Perlin Map Generator (seed {})", seed.0),
+                ..default()
+            }),
+            ..default()
+        }))
+        .insert_resource(seed)
+        .insert_resource(config)
         .init_resource::<controls::DragSelection>()
         .add_systems(Startup, (map_renderer::render_map, camera::setup_camera))
         .add_systems(
             Update,
             (
                 map_renderer::render_nature.run_if(run_once()),
+                map_renderer::render_points_of_interest.run_if(run_once()),
                 tank::spawn_tank
                     .run_if(run_once())
                     .after(map_renderer::render_nature),
@@ -1,5 +1,15 @@
 use bevy::ecs::{component::Component, system::Resource};
-use crate::biomes::*;
+use crate::climate::ClimateSettings;
+use crate::erosion::ErosionSettings;
+use crate::hillshade::Hillshade;
+use crate::regions::RegionId;
+use crate::settlements::PointOfInterest;
+use crate::map_generator::{seeded_rng, MapConfig, MapData, RngStream};
+use crate::noise_layer::DomainWarp;
+use crate::shape_mask::MapShape;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::str::FromStr;
 
 #[derive(Component)]
 pub struct TilePosition {
@@ -7,8 +17,13 @@ pub struct TilePosition {
     pub y: u32,
 }
 
+/// Water connected to the map border.
 #[derive(Component)]
-pub struct SeaTile;
+pub struct OceanTile;
+
+/// Water enclosed by land.
+#[derive(Component)]
+pub struct LakeTile;
 
 #[derive(Component)]
 pub struct LandTile;
@@ -16,10 +31,151 @@ pub struct LandTile;
 #[derive(Component)]
 pub struct ShoreTile;
 
+#[derive(Component)]
+pub struct RiverTile;
+
+#[derive(Component)]
+pub struct MountainTile;
+
+#[derive(Component)]
+pub struct CliffTile;
+
 #[derive(Component)]
 pub struct Nature;
 
+/// Side, in tiles, of the square a nature feature covers from its `TilePosition`.
+#[derive(Component)]
+pub struct Footprint {
+    pub size: u32,
+}
+
+// Labelled by the Bevy-free generator, so the component impl lives here.
+impl Component for RegionId {
+    type Storage = bevy::ecs::component::TableStorage;
+}
+
+impl Component for PointOfInterest {
+    type Storage = bevy::ecs::component::TableStorage;
+}
+
 #[derive(Resource)]
 pub struct Map {
-    pub biome: &'static Biome,
-}
\ No newline at end of file
+    pub data: MapData,
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct MapSeed(pub u64);
+
+fn has_flag(flag: &str) -> bool {
+    std::env::args().skip(1).any(|arg| arg == flag)
+}
+
+/// Reports a bad command line and exits, rather than panicking with a backtrace.
+fn exit_with_error(message: String) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(2)
+}
+
+/// Parses the value following `flag` on the command line, if present.
+fn parse_arg<T: FromStr>(flag: &str) -> Option<T> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            let value = args
+                .next()
+                .unwrap_or_else(|| exit_with_error(format!("{} requires a value", flag)));
+            let parsed = value
+                .parse()
+                .unwrap_or_else(|_| exit_with_error(format!("invalid {} value: {}", flag, value)));
+            return Some(parsed);
+        }
+    }
+    None
+}
+
+/// Like `parse_arg`, for sizes that must be at least 1.
+fn parse_size(flag: &str) -> Option<u32> {
+    let size = parse_arg(flag)?;
+    if size == 0 {
+        exit_with_error(format!("{} must be greater than 0", flag));
+    }
+    Some(size)
+}
+
+impl MapSeed {
+    /// Reads `--seed <u64>` from the command line, falling back to a random seed.
+    pub fn from_args() -> Self {
+        MapSeed(parse_arg("--seed").unwrap_or_else(|| rand::thread_rng().gen()))
+    }
+
+    pub fn rng(&self, stream: RngStream) -> StdRng {
+        seeded_rng(self.0, stream)
+    }
+}
+
+// `MapConfig` lives in the Bevy-free generator, so it is registered as a
+// resource here rather than with a derive.
+impl Resource for MapConfig {}
+
+impl MapConfig {
+    /// Reads `--width`, `--height`, `--tile-size`, `--warp`, `--shape`,
+    /// `--climate`, `--erosion`, `--min-region-size`, `--land-percent`, `--light`
+    /// and `--cliffs` from the command line, keeping the defaults for anything not given.
+    pub fn from_args() -> Self {
+        let default = MapConfig::default();
+        let config = MapConfig {
+            width: parse_size("--width").unwrap_or(default.width),
+            height: parse_size("--height").unwrap_or(default.height),
+            tile_size: parse_size("--tile-size").unwrap_or(default.tile_size),
+            warp: if has_flag("--warp") {
+                Some(DomainWarp {
+                    strength: 120.0,
+                    frequency: 0.0015,
+                })
+            } else {
+                default.warp
+            },
+            shape: parse_arg("--shape")
+                .map(|mask| MapShape {
+                    mask,
+                    strength: 1.0,
+                })
+                .or(default.shape),
+            climate: if has_flag("--climate") {
+                Some(ClimateSettings::default())
+            } else {
+                default.climate
+            },
+            erosion: if has_flag("--erosion") {
+                Some(ErosionSettings::default())
+            } else {
+                default.erosion
+            },
+            min_region_size: parse_arg("--min-region-size").unwrap_or(default.min_region_size),
+            land_fraction: parse_arg("--land-percent")
+                .map(|percent: f64| percent / 100.0)
+                .or(default.land_fraction),
+            hillshade: match parse_arg("--light") {
+                Some(azimuth) => Some(Hillshade {
+                    azimuth,
+                    ..Hillshade::default()
+                }),
+                None => default.hillshade,
+            },
+            cliff_share: if has_flag("--cliffs") {
+                Some(0.04)
+            } else {
+                default.cliff_share
+            },
+            ..default
+        };
+        let fits = |tiles: u32| tiles.checked_mul(config.tile_size).is_some();
+        if !fits(config.width) || !fits(config.height) {
+            exit_with_error(format!(
+                "a {}x{} map of {}-pixel tiles is too large",
+                config.width, config.height, config.tile_size
+            ));
+        }
+        config
+    }
+}
@@ -0,0 +1,1292 @@
+//! Bevy-free map generation: samples the terrain and nature noise, classifies
+//! tiles and places nature into plain grids that the renderer turns into sprites.
+
+use crate::autotile::autotile;
+use crate::biomes::*;
+use crate::climate::{biome_weights, BiomeWeights, ClimateLayer, ClimateSettings};
+use crate::erosion::{erode, ErosionSettings};
+use crate::grid::Grid;
+use crate::hillshade::Hillshade;
+use crate::hydrology::{drain, rasterize_rivers, Drainage, RiverSettings};
+use crate::noise_layer::{DomainWarp, NoiseLayer, NoiseSettings, WarpLayer};
+use crate::poisson::poisson_disc;
+use crate::regions::{connected_areas, label_regions, RegionKind, Regions};
+use crate::roads::{build_roads, RoadSettings};
+use crate::settlements::{place_points_of_interest, PoiKind, PoiSettings, PointOfInterest};
+use crate::shape_mask::{MapShape, MaskLayer};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Heights are rescaled so that every biome's thresholds fall on these, which
+// is what the colour bands are authored against.
+pub const SEA_THRESHOLD: f64 = -0.5;
+pub const LAND_THRESHOLD: f64 = -0.38;
+/// How far inside its band a height is pulled, so it stays clear of the
+/// neighbouring band's threshold.
+const BAND_MARGIN: f32 = 0.005;
+/// Share of land tiles, highest first, that become impassable mountain.
+const MOUNTAIN_SHARE: f32 = 0.06;
+/// Most rounds of turning river-fed depressions into lakes.
+const MAX_PIT_LAKE_PASSES: u32 = 4;
+/// How far a raised pit ends up above its rim, so the tile means stay clear
+/// of the rim height despite rounding and the pit drains out over it.
+const PIT_RIM_MARGIN: f32 = 1e-4;
+
+/// Pulls `height` into the band `tile` is classified from, so the elevation
+/// colours and any later reclassification agree with the tile type.
+pub fn clamp_to_band(tile: TileType, height: f32) -> f32 {
+    let (sea, land) = (SEA_THRESHOLD as f32, LAND_THRESHOLD as f32);
+    match tile {
+        TileType::Ocean | TileType::Lake => height.min(sea - BAND_MARGIN),
+        TileType::Shore => height.clamp(sea, land - BAND_MARGIN),
+        TileType::Land | TileType::River | TileType::Mountain | TileType::Cliff => height.max(land),
+    }
+}
+
+/// Independent random streams derived from the map seed, so adding draws to
+/// one stage never shifts the values seen by another.
+#[derive(Clone, Copy)]
+pub enum RngStream {
+    Terrain,
+    Texture,
+    Nature,
+    Spawn,
+    Erosion,
+    Decoration,
+    Settlements,
+}
+
+pub fn seeded_rng(seed: u64, stream: RngStream) -> StdRng {
+    StdRng::seed_from_u64(seed ^ (stream as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// Map dimensions in tiles, and the pixel size of each tile.
+#[derive(Clone)]
+pub struct MapConfig {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: u32,
+    /// Bends the terrain noise so coastlines and ridges swirl instead of
+    /// following plain noise contours.
+    pub warp: Option<DomainWarp>,
+    pub shape: Option<MapShape>,
+    /// Assigns a biome per tile from elevation, moisture and temperature;
+    /// without it the whole map shares one randomly chosen biome.
+    pub climate: Option<ClimateSettings>,
+    pub rivers: Option<RiverSettings>,
+    /// Weathers the raw noise into valleys and deltas before tiles are classified.
+    pub erosion: Option<ErosionSettings>,
+    /// Land or water regions with fewer tiles are merged into whatever
+    /// surrounds them; 0 or 1 keeps every region.
+    pub min_region_size: u32,
+    /// Fewest connected passable tiles units may be spawned on.
+    pub min_spawn_area: u32,
+    /// Fraction of the map that should be above sea level. When set, the sea
+    /// threshold is picked from the terrain's height distribution instead of
+    /// the biome's own thresholds.
+    pub land_fraction: Option<f64>,
+    /// Relief shading baked into the terrain textures.
+    pub hillshade: Option<Hillshade>,
+    /// Share of land tiles, steepest first, that become impassable cliffs.
+    /// A tile's steepness is the largest difference between its mean height
+    /// and a neighbouring land tile's.
+    pub cliff_share: Option<f32>,
+    /// Villages, outposts, oil derricks and ruins placed on the land.
+    pub points_of_interest: Option<PoiSettings>,
+    /// Roads joining the points of interest.
+    pub roads: Option<RoadSettings>,
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        MapConfig {
+            width: 100,
+            height: 100,
+            tile_size: 32,
+            warp: None,
+            shape: None,
+            climate: None,
+            rivers: Some(RiverSettings {
+                min_flow: 60,
+                max_width: 0.9,
+            }),
+            erosion: None,
+            min_region_size: 4,
+            min_spawn_area: 16,
+            land_fraction: None,
+            hillshade: Some(Hillshade::default()),
+            cliff_share: None,
+            points_of_interest: Some(PoiSettings::default()),
+            roads: Some(RoadSettings::default()),
+        }
+    }
+}
+
+impl MapConfig {
+    pub fn world_width(&self) -> f32 {
+        (self.width * self.tile_size) as f32
+    }
+
+    pub fn world_height(&self) -> f32 {
+        (self.height * self.tile_size) as f32
+    }
+
+    /// World-space centre of a `size` x `size` block of tiles whose top-left
+    /// tile is (`tile_x`, `tile_y`).
+    pub fn footprint_center(&self, tile_x: u32, tile_y: u32, size: u32) -> (f32, f32) {
+        let (x, y) = self.tile_center(tile_x, tile_y);
+        let offset = (size - 1) as f32 * self.tile_size as f32 / 2.0;
+        (x + offset, y - offset)
+    }
+
+    /// The tile under a world-space position, if it is on the map.
+    pub fn tile_at(&self, position: (f32, f32)) -> Option<(u32, u32)> {
+        let tile_size = self.tile_size as f32;
+        let (column, row_from_bottom) = (position.0 / tile_size, position.1 / tile_size);
+        if column < 0.0 || row_from_bottom < 0.0 {
+            return None;
+        }
+        let (x, row_from_bottom) = (column as u32, row_from_bottom as u32);
+        if x >= self.width || row_from_bottom >= self.height {
+            return None;
+        }
+        Some((x, self.height - 1 - row_from_bottom))
+    }
+
+    /// World-space centre of a tile; tile rows run top to bottom while world y runs upwards.
+    pub fn tile_center(&self, tile_x: u32, tile_y: u32) -> (f32, f32) {
+        let tile_size = self.tile_size as f32;
+        (
+            tile_x as f32 * tile_size + tile_size / 2.0,
+            (self.height - 1 - tile_y) as f32 * tile_size + tile_size / 2.0,
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileType {
+    Ocean,
+    Lake,
+    Shore,
+    Land,
+    River,
+    Mountain,
+    Cliff,
+}
+
+impl TileType {
+    pub fn is_water(&self) -> bool {
+        matches!(self, TileType::Ocean | TileType::Lake)
+    }
+}
+
+/// A nature sprite and the biome whose spawn table placed it, which near a
+/// biome border may differ from the tile's own biome.
+#[derive(Clone, Copy)]
+pub struct NatureFeature {
+    /// File name inside the biome's asset folder.
+    pub asset: &'static str,
+    pub biome: &'static Biome,
+    /// Side of the square footprint, extending right and down from the
+    /// tile the feature is recorded at.
+    pub size: u32,
+    /// Copied from the spawn entry; see `SpawnEntry::rotates`.
+    pub rotates: bool,
+}
+
+pub struct MapData {
+    pub biome_weights: Grid<BiomeWeights>,
+    /// Terrain noise sampled once per pixel.
+    pub heightmap: Grid<f32>,
+    /// Hillshade brightness of each pixel, packed by `Hillshade::shade_map`,
+    /// or `None` without hillshading. Shaded from the heightmap as generated,
+    /// before each pixel was pulled into its tile's band, so it follows the
+    /// terrain rather than the tiles. Only meaningful on land.
+    pub shading: Option<Grid<u8>>,
+    pub tiles: Grid<TileType>,
+    pub regions: Regions,
+    /// Pixels covered by river water, which may spill past river tiles' edges.
+    pub river_pixels: Grid<bool>,
+    /// Features keyed by the top-left tile of their footprint.
+    pub nature: Grid<Option<NatureFeature>>,
+    /// Tiles covered by any feature's footprint.
+    pub nature_footprints: Grid<bool>,
+    pub points_of_interest: Vec<PointOfInterest>,
+    /// Tiles covered by any point of interest's footprint.
+    pub poi_footprints: Grid<bool>,
+    /// Tiles a road runs over.
+    pub roads: Grid<bool>,
+    /// How much of each pixel a road covers, from 0 to 255.
+    pub road_pixels: Grid<u8>,
+}
+
+/// Returned when no landmass is large enough to spawn units on.
+#[derive(Debug)]
+pub struct NoSpawnArea {
+    pub min_size: u32,
+    pub largest: u32,
+}
+
+impl std::fmt::Display for NoSpawnArea {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "no connected passable area of at least {} tiles (largest has {})",
+            self.min_size, self.largest
+        )
+    }
+}
+
+impl MapData {
+    pub fn generate(seed: u64, config: &MapConfig) -> Self {
+        let mut rng = seeded_rng(seed, RngStream::Terrain);
+        let biome = get_random_biome(&mut rng);
+        let terrain = NoiseLayer::new(&biome.terrain_noise, rng.gen());
+        let warp = config.warp.map(|warp| WarpLayer::new(&warp, rng.gen()));
+        let mask = config.shape.map(|shape| MaskLayer::new(&shape, &mut rng));
+        let climate = config
+            .climate
+            .map(|climate| ClimateLayer::new(&climate, &mut rng));
+
+        let pixel_width = config.width * config.tile_size;
+        let pixel_height = config.height * config.tile_size;
+        let mut heightmap = Grid::from_fn(pixel_width, pixel_height, |x, y| {
+            let (warped_x, warped_y) = match &warp {
+                Some(warp) => warp.apply(x as f64, y as f64),
+                None => (x as f64, y as f64),
+            };
+            let noise = terrain.get(warped_x, warped_y);
+            let noise = match &mask {
+                Some(mask) => mask.apply(
+                    noise,
+                    x as f64 / pixel_width as f64,
+                    y as f64 / pixel_height as f64,
+                ),
+                None => noise,
+            };
+            noise as f32
+        });
+        // Sea level is global, so when the climate places several biomes on
+        // the map their thresholds are blended by how much of it each covers.
+        let thresholds = match (config.land_fraction, &climate) {
+            (Some(land_fraction), _) => {
+                thresholds_for_land_fraction(&heightmap, land_fraction, &biome.thresholds)
+            }
+            (None, Some(climate)) => {
+                climate_thresholds(&heightmap, climate, config.tile_size, &biome.thresholds)
+            }
+            (None, None) => biome.thresholds,
+        };
+        normalize_heights(&mut heightmap, &thresholds);
+        if let Some(erosion) = config.erosion {
+            erode(
+                &mut heightmap,
+                &erosion,
+                &mut seeded_rng(seed, RngStream::Erosion),
+            );
+        }
+        // Shaded before heights are pulled into their tiles' bands, which
+        // leaves steps along every coast that would shade as outlines.
+        let shading = config
+            .hillshade
+            .map(|hillshade| hillshade.shade_map(&heightmap));
+        let mut tiles = Grid::from_fn(config.width, config.height, |x, y| {
+            classify_tile(&heightmap, config.tile_size, x, y)
+        });
+        if config.min_region_size > 1 {
+            remove_small_regions(
+                &mut tiles,
+                &mut heightmap,
+                config.tile_size,
+                config.min_region_size,
+            );
+        }
+        let mut regions = label_regions(&tiles);
+        mark_lakes(&mut tiles, &regions);
+        autotile(&tiles, &mut heightmap, config.tile_size);
+        let mut elevations = tile_elevations(&heightmap, config.tile_size);
+        let uplands = unmasked_elevations(&elevations, mask.as_ref(), config.tile_size);
+        let mountain_level = mountain_level(&tiles, &uplands);
+        classify_highlands(
+            &mut tiles,
+            &elevations,
+            &uplands,
+            mountain_level,
+            config.cliff_share,
+        );
+        let river_pixels = match config.rivers {
+            Some(rivers) => {
+                let mut drainage = drain(&elevations, &tiles);
+                // Filling a depression can leave the water spilling out into
+                // another one below its rim, so this takes a few passes.
+                let mut filled = 0;
+                while filled < MAX_PIT_LAKE_PASSES
+                    && fill_pit_lakes(
+                        &mut tiles,
+                        &mut heightmap,
+                        config.tile_size,
+                        &elevations,
+                        &drainage,
+                        rivers.min_flow,
+                        config.min_region_size,
+                    )
+                {
+                    autotile(&tiles, &mut heightmap, config.tile_size);
+                    elevations = tile_elevations(&heightmap, config.tile_size);
+                    drainage = drain(&elevations, &tiles);
+                    filled += 1;
+                }
+                if filled > 0 {
+                    // New lakes can cut off slivers of land smaller than the
+                    // regions cleaned up above.
+                    if config.min_region_size > 1 {
+                        remove_small_regions(
+                            &mut tiles,
+                            &mut heightmap,
+                            config.tile_size,
+                            config.min_region_size,
+                        );
+                        autotile(&tiles, &mut heightmap, config.tile_size);
+                        elevations = tile_elevations(&heightmap, config.tile_size);
+                        drainage = drain(&elevations, &tiles);
+                    }
+                    regions = label_regions(&tiles);
+                    mark_lakes(&mut tiles, &regions);
+                }
+                for y in 0..config.height {
+                    for x in 0..config.width {
+                        if matches!(tiles.get(x, y), TileType::Shore | TileType::Land)
+                            && *drainage.flow.get(x, y) >= rivers.min_flow
+                        {
+                            tiles.set(x, y, TileType::River);
+                        }
+                    }
+                }
+                rasterize_rivers(&drainage, &tiles, &rivers, config.tile_size)
+            }
+            None => Grid::from_fn(pixel_width, pixel_height, |_, _| false),
+        };
+        let biomes = match &climate {
+            Some(climate) => Grid::from_fn(config.width, config.height, |x, y| {
+                let (pixel_x, pixel_y) = tile_center_pixel(config.tile_size, x, y);
+                climate.biome_at(
+                    pixel_x,
+                    pixel_y,
+                    (y as f64 + 0.5) / config.height as f64,
+                    *elevations.get(x, y) as f64,
+                )
+            }),
+            None => Grid::from_fn(config.width, config.height, |_, _| biome),
+        };
+        let transition_width = config.climate.map_or(0, |climate| climate.transition_width);
+        let biome_weights = biome_weights(&biomes, transition_width);
+        let points_of_interest = match &config.points_of_interest {
+            Some(settings) => place_points_of_interest(
+                &tiles,
+                &unmasked_elevations(&elevations, mask.as_ref(), config.tile_size),
+                mountain_level,
+                &biomes,
+                settings,
+                &mut seeded_rng(seed, RngStream::Settlements),
+            ),
+            None => Vec::new(),
+        };
+        let mut poi_footprints = Grid::from_fn(config.width, config.height, |_, _| false);
+        for poi in &points_of_interest {
+            let size = poi.kind.size();
+            for y in poi.y..poi.y + size {
+                for x in poi.x..poi.x + size {
+                    poi_footprints.set(x, y, true);
+                }
+            }
+        }
+        let (roads, road_pixels) = match &config.roads {
+            Some(settings) => {
+                // Ruins are abandoned, so no road leads to them.
+                let endpoints: Vec<_> = points_of_interest
+                    .iter()
+                    .filter(|poi| poi.kind != PoiKind::Ruins)
+                    .map(|poi| (poi.x + poi.kind.size() / 2, poi.y + poi.kind.size() - 1))
+                    .collect();
+                build_roads(
+                    &tiles,
+                    &elevations,
+                    &poi_footprints,
+                    &endpoints,
+                    settings,
+                    config.tile_size,
+                )
+            }
+            None => (
+                Grid::from_fn(config.width, config.height, |_, _| false),
+                Grid::from_fn(pixel_width, pixel_height, |_, _| 0),
+            ),
+        };
+        // Nature keeps clear of settlements and roads rather than growing
+        // through them.
+        let reserved = Grid::from_fn(config.width, config.height, |x, y| {
+            *roads.get(x, y) || *poi_footprints.get(x, y)
+        });
+        let (nature, nature_footprints) = place_nature(
+            &biome_weights,
+            &tiles,
+            &reserved,
+            &mut seeded_rng(seed, RngStream::Nature),
+        );
+
+        MapData {
+            biome_weights,
+            heightmap,
+            shading,
+            tiles,
+            regions,
+            river_pixels,
+            nature,
+            nature_footprints,
+            points_of_interest,
+            poi_footprints,
+            roads,
+            road_pixels,
+        }
+    }
+
+    /// Whether ground units can stand on a tile; rivers are shallow enough to
+    /// ford, while nature and points of interest block their footprints.
+    pub fn is_passable(&self, x: u32, y: u32) -> bool {
+        matches!(
+            self.tiles.get(x, y),
+            TileType::Shore | TileType::Land | TileType::River
+        ) && !*self.nature_footprints.get(x, y)
+            && !*self.poi_footprints.get(x, y)
+    }
+
+    /// The tiles of the largest connected passable area, in row order, so
+    /// every unit spawned on it can reach every other.
+    pub fn spawn_area(&self, min_size: u32) -> Result<Vec<(u32, u32)>, NoSpawnArea> {
+        let largest = connected_areas(self.tiles.width, self.tiles.height, |x, y| {
+            self.is_passable(x, y)
+        })
+        .into_iter()
+        .max_by_key(|area| area.len())
+        .unwrap_or_default();
+        if largest.is_empty() || (largest.len() as u32) < min_size {
+            return Err(NoSpawnArea {
+                min_size,
+                largest: largest.len() as u32,
+            });
+        }
+        Ok(largest)
+    }
+
+    /// Blends the biome colours at a pixel by bilinearly interpolating the
+    /// biome weights of the surrounding tile centres.
+    pub fn pixel_color(&self, pixel_x: u32, pixel_y: u32) -> [u8; 4] {
+        let noise = *self.heightmap.get(pixel_x, pixel_y) as f64;
+        let tile_size = (self.heightmap.width / self.tiles.width) as f32;
+        let max_x = self.tiles.width - 1;
+        let max_y = self.tiles.height - 1;
+
+        let fx = ((pixel_x as f32 + 0.5) / tile_size - 0.5).max(0.0);
+        let fy = ((pixel_y as f32 + 0.5) / tile_size - 0.5).max(0.0);
+        let (x0, y0) = ((fx as u32).min(max_x), (fy as u32).min(max_y));
+        let (x1, y1) = ((x0 + 1).min(max_x), (y0 + 1).min(max_y));
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+
+        // Mountain and cliff tints fade across tile edges the same way.
+        let mut weights = [0.0; BIOMES.len()];
+        let (mut mountain, mut cliff) = (0.0, 0.0);
+        for (tile_x, tile_y, share) in [
+            (x0, y0, (1.0 - tx) * (1.0 - ty)),
+            (x1, y0, tx * (1.0 - ty)),
+            (x0, y1, (1.0 - tx) * ty),
+            (x1, y1, tx * ty),
+        ] {
+            for (total, weight) in weights
+                .iter_mut()
+                .zip(self.biome_weights.get(tile_x, tile_y))
+            {
+                *total += weight * share;
+            }
+            match self.tiles.get(tile_x, tile_y) {
+                TileType::Mountain => mountain += share as f64,
+                TileType::Cliff => cliff += share as f64,
+                _ => {}
+            }
+        }
+
+        let is_river = *self.river_pixels.get(pixel_x, pixel_y);
+        let mut color = [0.0; 4];
+        for (biome, weight) in BIOMES.iter().zip(weights) {
+            if weight > 0.0 {
+                let biome_color = if is_river {
+                    biome.river_color.to_array()
+                } else {
+                    let mut terrain = colourize_noise(biome, noise);
+                    if mountain > 0.0 {
+                        terrain = tint(terrain, &biome.mountain_color, 0.5 * mountain);
+                    }
+                    if cliff > 0.0 {
+                        terrain = tint(terrain, &biome.cliff_color, 0.6 * cliff);
+                    }
+                    terrain
+                };
+                let biome_color = match *self.road_pixels.get(pixel_x, pixel_y) {
+                    0 => biome_color,
+                    coverage => tint(
+                        biome_color,
+                        &biome.road_color,
+                        coverage as f64 / 255.0 * 0.85,
+                    ),
+                };
+                for (channel, value) in color.iter_mut().zip(biome_color) {
+                    *channel += value as f32 * weight;
+                }
+            }
+        }
+        color.map(|channel| channel.round() as u8)
+    }
+}
+
+fn get_random_biome(rng: &mut impl Rng) -> &'static Biome {
+    BIOMES[rng.gen_range(0..BIOMES.len())]
+}
+
+fn tile_center_pixel(tile_size: u32, tile_x: u32, tile_y: u32) -> (f64, f64) {
+    let half = tile_size as f64 / 2.0;
+    (
+        (tile_x * tile_size) as f64 + half,
+        (tile_y * tile_size) as f64 + half,
+    )
+}
+
+/// Mean terrain height of each tile.
+fn tile_elevations(heightmap: &Grid<f32>, tile_size: u32) -> Grid<f32> {
+    Grid::from_fn(
+        heightmap.width / tile_size,
+        heightmap.height / tile_size,
+        |tile_x, tile_y| {
+            let mut sum = 0.0;
+            for pixel_y in tile_y * tile_size..(tile_y + 1) * tile_size {
+                for pixel_x in tile_x * tile_size..(tile_x + 1) * tile_size {
+                    sum += *heightmap.get(pixel_x, pixel_y);
+                }
+            }
+            sum / (tile_size * tile_size) as f32
+        },
+    )
+}
+
+/// Picks the sea threshold whose share of lower pixels is `1 - land_fraction`,
+/// keeping the biome's shore width above it.
+fn thresholds_for_land_fraction(
+    heightmap: &Grid<f32>,
+    land_fraction: f64,
+    biome_thresholds: &Thresholds,
+) -> Thresholds {
+    const BINS: usize = 1024;
+    let (mut min, mut max) = (f32::MAX, f32::MIN);
+    for y in 0..heightmap.height {
+        for x in 0..heightmap.width {
+            let height = *heightmap.get(x, y);
+            min = min.min(height);
+            max = max.max(height);
+        }
+    }
+    let bin_size = ((max - min) / BINS as f32).max(f32::EPSILON);
+    let mut histogram = [0u32; BINS];
+    for y in 0..heightmap.height {
+        for x in 0..heightmap.width {
+            let bin = ((heightmap.get(x, y) - min) / bin_size) as usize;
+            histogram[bin.min(BINS - 1)] += 1;
+        }
+    }
+
+    let water_pixels =
+        (1.0 - land_fraction.clamp(0.0, 1.0)) * (heightmap.width * heightmap.height) as f64;
+    let mut below = 0.0;
+    let mut sea = max as f64;
+    for (bin, count) in histogram.iter().enumerate() {
+        if below + *count as f64 > water_pixels {
+            let within = (water_pixels - below) / *count as f64;
+            sea = min as f64 + (bin as f64 + within) * bin_size as f64;
+            break;
+        }
+        below += *count as f64;
+    }
+    Thresholds {
+        sea,
+        land: sea + (biome_thresholds.land - biome_thresholds.sea),
+    }
+}
+
+/// Remaps raw heights so `thresholds` land on `SEA_THRESHOLD` and
+/// `LAND_THRESHOLD`: heights below and above the shore band are shifted and
+/// the band itself is stretched between them.
+fn normalize_heights(heightmap: &mut Grid<f32>, thresholds: &Thresholds) {
+    for y in 0..heightmap.height {
+        for x in 0..heightmap.width {
+            let height = *heightmap.get(x, y);
+            heightmap.set(x, y, normalize_height(height, thresholds));
+        }
+    }
+}
+
+fn normalize_height(height: f32, thresholds: &Thresholds) -> f32 {
+    let (sea, land) = (thresholds.sea as f32, thresholds.land as f32);
+    let (target_sea, target_land) = (SEA_THRESHOLD as f32, LAND_THRESHOLD as f32);
+    if height < sea {
+        height - sea + target_sea
+    } else if height < land {
+        target_sea + (height - sea) / (land - sea) * (target_land - target_sea)
+    } else {
+        height - land + target_land
+    }
+}
+
+/// Averages the thresholds of the biomes the climate would place on each
+/// tile, judging elevation by the base biome's thresholds since the final
+/// sea level is not known yet.
+fn climate_thresholds(
+    heightmap: &Grid<f32>,
+    climate: &ClimateLayer,
+    tile_size: u32,
+    base: &Thresholds,
+) -> Thresholds {
+    let (width, height) = (heightmap.width / tile_size, heightmap.height / tile_size);
+    if width * height == 0 {
+        return *base;
+    }
+    let (mut sea, mut land) = (0.0, 0.0);
+    for y in 0..height {
+        for x in 0..width {
+            let (pixel_x, pixel_y) = tile_center_pixel(tile_size, x, y);
+            let elevation = normalize_height(*heightmap.get(pixel_x as u32, pixel_y as u32), base);
+            let biome = climate.biome_at(
+                pixel_x,
+                pixel_y,
+                (y as f64 + 0.5) / height as f64,
+                elevation as f64,
+            );
+            sea += biome.thresholds.sea;
+            land += biome.thresholds.land;
+        }
+    }
+    let tiles = (width * height) as f64;
+    Thresholds {
+        sea: sea / tiles,
+        land: land / tiles,
+    }
+}
+
+/// Tile elevations with the shape mask's bias taken back out, so heights
+/// compare the terrain itself rather than where the mask forced land.
+fn unmasked_elevations(
+    elevations: &Grid<f32>,
+    mask: Option<&MaskLayer>,
+    tile_size: u32,
+) -> Grid<f32> {
+    let (pixel_width, pixel_height) = (elevations.width * tile_size, elevations.height * tile_size);
+    Grid::from_fn(elevations.width, elevations.height, |x, y| {
+        let bias = mask.map_or(0.0, |mask| {
+            let (pixel_x, pixel_y) = tile_center_pixel(tile_size, x, y);
+            mask.apply(
+                0.0,
+                pixel_x / pixel_width as f64,
+                pixel_y / pixel_height as f64,
+            )
+        });
+        elevations.get(x, y) - bias as f32
+    })
+}
+
+/// Height from which the highest `MOUNTAIN_SHARE` of land tiles rise, so
+/// every map gets its share of mountains whatever its sea level or shape;
+/// infinite when the map has too little land for any.
+fn mountain_level(tiles: &Grid<TileType>, uplands: &Grid<f32>) -> f32 {
+    let mut heights = Vec::new();
+    for y in 0..tiles.height {
+        for x in 0..tiles.width {
+            if *tiles.get(x, y) == TileType::Land {
+                heights.push(*uplands.get(x, y));
+            }
+        }
+    }
+    let mountains = (heights.len() as f32 * MOUNTAIN_SHARE).round() as usize;
+    if mountains == 0 {
+        return f32::INFINITY;
+    }
+    heights.sort_by(f32::total_cmp);
+    heights[heights.len() - mountains]
+}
+
+/// Raises land from `mountain_level` up to mountains and, when `cliff_share`
+/// is set, marks that share of the remaining land, steepest first, as cliff.
+fn classify_highlands(
+    tiles: &mut Grid<TileType>,
+    elevations: &Grid<f32>,
+    uplands: &Grid<f32>,
+    mountain_level: f32,
+    cliff_share: Option<f32>,
+) {
+    for y in 0..tiles.height {
+        for x in 0..tiles.width {
+            if *tiles.get(x, y) == TileType::Land && *uplands.get(x, y) >= mountain_level {
+                tiles.set(x, y, TileType::Mountain);
+            }
+        }
+    }
+    let Some(cliff_share) = cliff_share else {
+        return;
+    };
+    // Measured before any cliff is placed so new cliffs do not hide the
+    // slopes next to them. Like mountains, cliffs are picked by rank rather
+    // than by a fixed gradient, which would depend on the biome's relief.
+    let mut slopes = Vec::new();
+    for y in 0..tiles.height {
+        for x in 0..tiles.width {
+            if *tiles.get(x, y) != TileType::Land {
+                continue;
+            }
+            let elevation = *elevations.get(x, y);
+            let slope = tiles
+                .neighbours8(x, y)
+                .filter(|&(nx, ny)| !tiles.get(nx, ny).is_water())
+                .map(|(nx, ny)| (elevations.get(nx, ny) - elevation).abs())
+                .fold(0.0, f32::max);
+            slopes.push((slope, x, y));
+        }
+    }
+    let cliffs = (slopes.len() as f32 * cliff_share).round() as usize;
+    slopes.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for &(_, x, y) in &slopes[..cliffs.min(slopes.len())] {
+        tiles.set(x, y, TileType::Cliff);
+    }
+}
+
+/// Turns every depression a river would run through into a lake, so rivers
+/// end in it and leave over its rim instead of climbing out of the pit. The
+/// lake bed is sunk below sea level by the water depth, so deeper pits read
+/// as deeper water. Pits of fewer than `min_size` tiles would only make
+/// puddles, so they are raised level with their rim instead. Returns whether
+/// any pit was filled.
+fn fill_pit_lakes(
+    tiles: &mut Grid<TileType>,
+    heightmap: &mut Grid<f32>,
+    tile_size: u32,
+    elevations: &Grid<f32>,
+    drainage: &Drainage,
+    min_flow: u32,
+    min_size: u32,
+) -> bool {
+    let pits = connected_areas(tiles.width, tiles.height, |x, y| {
+        matches!(tiles.get(x, y), TileType::Shore | TileType::Land)
+            && drainage.spill.get(x, y) > elevations.get(x, y)
+    });
+    let mut filled = false;
+    for pit in pits {
+        if !pit
+            .iter()
+            .any(|&(x, y)| *drainage.flow.get(x, y) >= min_flow)
+        {
+            continue;
+        }
+        let lake = pit.len() as u32 >= min_size;
+        for (x, y) in pit {
+            let spill = *drainage.spill.get(x, y);
+            if lake {
+                tiles.set(x, y, TileType::Lake);
+            }
+            for pixel_y in y * tile_size..(y + 1) * tile_size {
+                for pixel_x in x * tile_size..(x + 1) * tile_size {
+                    let height = *heightmap.get(pixel_x, pixel_y);
+                    let filled = if lake {
+                        SEA_THRESHOLD as f32 - (spill - height).max(0.0)
+                    } else {
+                        height.max(spill + PIT_RIM_MARGIN)
+                    };
+                    heightmap.set(pixel_x, pixel_y, filled);
+                }
+            }
+        }
+        filled = true;
+    }
+    filled
+}
+
+/// Retypes the water of every enclosed region as lake.
+fn mark_lakes(tiles: &mut Grid<TileType>, regions: &Regions) {
+    for y in 0..tiles.height {
+        for x in 0..tiles.width {
+            if regions.region_at(x, y).kind == RegionKind::Lake {
+                tiles.set(x, y, TileType::Lake);
+            }
+        }
+    }
+}
+
+/// Turns undersized regions into the tile type most common along their
+/// border, and pulls their pixel heights into that type's band so the
+/// texture agrees with the new classification.
+fn remove_small_regions(
+    tiles: &mut Grid<TileType>,
+    heightmap: &mut Grid<f32>,
+    tile_size: u32,
+    min_size: u32,
+) {
+    let regions = label_regions(tiles);
+    let mut surroundings = vec![[0u32; 3]; regions.regions.len()];
+    for y in 0..tiles.height {
+        for x in 0..tiles.width {
+            let id = *regions.ids.get(x, y);
+            if regions.regions[id.0 as usize].size >= min_size {
+                continue;
+            }
+            for (nx, ny) in tiles.neighbours4(x, y) {
+                if *regions.ids.get(nx, ny) != id {
+                    let slot = match tiles.get(nx, ny) {
+                        TileType::Shore => 1,
+                        TileType::Land => 2,
+                        _ => 0,
+                    };
+                    surroundings[id.0 as usize][slot] += 1;
+                }
+            }
+        }
+    }
+
+    for tile_y in 0..tiles.height {
+        for tile_x in 0..tiles.width {
+            let counts = surroundings[regions.ids.get(tile_x, tile_y).0 as usize];
+            if counts == [0; 3] {
+                continue;
+            }
+            let slot = (0..3).max_by_key(|&slot| counts[slot]).unwrap();
+            let tile_type = match slot {
+                0 => TileType::Ocean,
+                1 => TileType::Shore,
+                _ => TileType::Land,
+            };
+            tiles.set(tile_x, tile_y, tile_type);
+            for pixel_y in tile_y * tile_size..(tile_y + 1) * tile_size {
+                for pixel_x in tile_x * tile_size..(tile_x + 1) * tile_size {
+                    let value = clamp_to_band(tile_type, *heightmap.get(pixel_x, pixel_y));
+                    heightmap.set(pixel_x, pixel_y, value);
+                }
+            }
+        }
+    }
+}
+
+fn classify_noise(noise: f64) -> TileType {
+    match noise {
+        n if n < SEA_THRESHOLD => TileType::Ocean,
+        n if n < LAND_THRESHOLD => TileType::Shore,
+        _ => TileType::Land,
+    }
+}
+
+/// A tile takes the type covering at least half its pixels, defaulting to land.
+fn classify_tile(heightmap: &Grid<f32>, tile_size: u32, tile_x: u32, tile_y: u32) -> TileType {
+    let mut sea_count = 0;
+    let mut shore_count = 0;
+    let mut land_count = 0;
+
+    for tile_pixel_y in 0..tile_size {
+        for tile_pixel_x in 0..tile_size {
+            let noise = *heightmap.get(
+                tile_x * tile_size + tile_pixel_x,
+                tile_y * tile_size + tile_pixel_y,
+            );
+            match classify_noise(noise as f64) {
+                TileType::Ocean => sea_count += 1,
+                TileType::Shore => shore_count += 1,
+                _ => land_count += 1,
+            }
+        }
+    }
+
+    // A type holds the tile when it covers at least half of its pixels.
+    let total = tile_size * tile_size;
+    match (
+        sea_count * 2 >= total,
+        shore_count * 2 >= total,
+        land_count * 2 >= total,
+    ) {
+        (true, false, false) => TileType::Ocean,
+        (false, true, false) => TileType::Shore,
+        _ => TileType::Land,
+    }
+}
+
+pub fn generate_tile_map(
+    width: u32,
+    height: u32,
+    settings: &NoiseSettings,
+    rng: &mut impl Rng,
+) -> Grid<f64> {
+    let layer = NoiseLayer::new(settings, rng.gen());
+    Grid::from_fn(width, height, |x, y| layer.get(x as f64, y as f64))
+}
+
+/// Runs every biome's spawn tables over the map. Near a border each biome
+/// only fills its share of the spots, in proportion to its weight there.
+/// Tiles in `reserved` are left bare. Returns the features and the tiles
+/// their footprints cover.
+fn place_nature(
+    biome_weights: &Grid<BiomeWeights>,
+    tiles: &Grid<TileType>,
+    reserved: &Grid<bool>,
+    rng: &mut impl Rng,
+) -> (Grid<Option<NatureFeature>>, Grid<bool>) {
+    // Every biome gets its own density field, built in a fixed order so the
+    // draws stay reproducible whichever biomes the map contains.
+    let density_maps: Vec<_> = BIOMES
+        .iter()
+        .map(|biome| generate_tile_map(tiles.width, tiles.height, &biome.nature_noise, rng))
+        .collect();
+
+    let mut nature = Grid::from_fn(tiles.width, tiles.height, |_, _| None);
+    let mut footprints = Grid::from_fn(tiles.width, tiles.height, |_, _| false);
+    for (index, biome) in BIOMES.iter().enumerate() {
+        // Smoothstep of the field, so dense cover has cores and clearings.
+        let cover_at = |x: u32, y: u32| {
+            let field = (*density_maps[index].get(x, y) as f32 + 1.0) / 2.0;
+            let t = ((field - 0.4) / 0.3).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        };
+
+        for table in biome.nature {
+            let mut counts = vec![0; table.entries.len()];
+            let points = match table.cover {
+                Cover::Core => (0..tiles.height)
+                    .flat_map(|y| (0..tiles.width).map(move |x| (x as f32, y as f32)))
+                    .collect(),
+                _ => poisson_disc(
+                    tiles.width as f32,
+                    tiles.height as f32,
+                    table.scatter.spacing,
+                    rng,
+                ),
+            };
+            for (x, y) in points {
+                let (x, y) = (x as u32, y as u32);
+                if *tiles.get(x, y) != table.tile || *footprints.get(x, y) || *reserved.get(x, y) {
+                    continue;
+                }
+                let cover = match table.cover {
+                    Cover::Dense => cover_at(x, y),
+                    Cover::Sparse => 1.0 - cover_at(x, y),
+                    Cover::Even => 1.0,
+                    Cover::Core => f32::from(cover_at(x, y) >= 0.9),
+                };
+                let chance = table.scatter.density * cover * biome_weights.get(x, y)[index];
+                if rng.gen::<f32>() >= chance {
+                    continue;
+                }
+                let Some(entry) = pick_spawn_entry(table.entries, &counts, rng) else {
+                    break;
+                };
+                let size = table.entries[entry].size;
+                let fits = x + size <= tiles.width
+                    && y + size <= tiles.height
+                    && (y..y + size).all(|footprint_y| {
+                        (x..x + size).all(|footprint_x| {
+                            *tiles.get(footprint_x, footprint_y) == table.tile
+                                && !*footprints.get(footprint_x, footprint_y)
+                                && !*reserved.get(footprint_x, footprint_y)
+                        })
+                    });
+                if !fits {
+                    continue;
+                }
+                counts[entry] += 1;
+                for footprint_y in y..y + size {
+                    for footprint_x in x..x + size {
+                        footprints.set(footprint_x, footprint_y, true);
+                    }
+                }
+                nature.set(
+                    x,
+                    y,
+                    Some(NatureFeature {
+                        asset: table.entries[entry].asset,
+                        biome,
+                        size,
+                        rotates: table.entries[entry].rotates,
+                    }),
+                );
+            }
+        }
+    }
+    (nature, footprints)
+}
+
+/// Draws an entry in proportion to its weight, skipping entries that have
+/// reached their `max_count`. `None` once every entry is used up.
+fn pick_spawn_entry(entries: &[SpawnEntry], counts: &[u32], rng: &mut impl Rng) -> Option<usize> {
+    let available = |index: usize| {
+        entries[index]
+            .max_count
+            .is_none_or(|max_count| counts[index] < max_count)
+    };
+    let total: f32 = (0..entries.len())
+        .filter(|&index| available(index))
+        .map(|index| entries[index].weight)
+        .sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0.0..total);
+    for index in (0..entries.len()).filter(|&index| available(index)) {
+        if roll < entries[index].weight {
+            return Some(index);
+        }
+        roll -= entries[index].weight;
+    }
+    (0..entries.len()).rev().find(|&index| available(index))
+}
+
+/// Mixes `amount` of `color` into `base`.
+fn tint(base: [u8; 4], color: &Color, amount: f64) -> [u8; 4] {
+    let [r, g, b, a] = base;
+    Color { r, g, b, a }.lerp(color, amount)
+}
+
+pub fn colourize_noise(biome: &Biome, noise: f64) -> [u8; 4] {
+    let bands = biome.bands;
+    match bands.iter().position(|band| noise < band.height) {
+        Some(0) => bands[0].color.to_array(),
+        Some(upper) => {
+            let (low, high) = (&bands[upper - 1], &bands[upper]);
+            let t = (noise - low.height) / (high.height - low.height);
+            low.color.lerp(&high.color, t)
+        }
+        None => bands[bands.len() - 1].color.to_array(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(width: u32, height: u32, tile_size: u32) -> MapConfig {
+        MapConfig {
+            width,
+            height,
+            tile_size,
+            ..MapConfig::default()
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_map() {
+        let config = config(24, 16, 4);
+        let (first, second) = (MapData::generate(7, &config), MapData::generate(7, &config));
+        assert_eq!(first.heightmap, second.heightmap);
+        assert_eq!(first.tiles, second.tiles);
+        let features = |data: &MapData| {
+            (0..data.nature.height)
+                .flat_map(|y| (0..data.nature.width).map(move |x| (x, y)))
+                .filter(|&(x, y)| data.nature.get(x, y).is_some())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(features(&first), features(&second));
+        assert_eq!(first.nature_footprints, second.nature_footprints);
+        let sites = |data: &MapData| {
+            data.points_of_interest
+                .iter()
+                .map(|poi| (poi.kind, poi.x, poi.y))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sites(&first), sites(&second));
+        assert_eq!(first.roads, second.roads);
+        assert_eq!(first.pixel_color(10, 10), second.pixel_color(10, 10));
+    }
+
+    #[test]
+    fn different_seeds_give_different_terrain() {
+        let config = config(24, 16, 4);
+        assert_ne!(
+            MapData::generate(1, &config).heightmap,
+            MapData::generate(2, &config).heightmap
+        );
+    }
+
+    #[test]
+    fn classify_tile_takes_the_majority_type() {
+        let uniform = |height: f32| Grid::from_fn(2, 2, |_, _| height);
+        assert_eq!(classify_tile(&uniform(-0.9), 2, 0, 0), TileType::Ocean);
+        assert_eq!(classify_tile(&uniform(-0.45), 2, 0, 0), TileType::Shore);
+        assert_eq!(classify_tile(&uniform(0.2), 2, 0, 0), TileType::Land);
+
+        // Three of four pixels under the sea threshold.
+        let mostly_sea = Grid::from_fn(2, 2, |x, y| if x + y == 2 { 0.2 } else { -0.9 });
+        assert_eq!(classify_tile(&mostly_sea, 2, 0, 0), TileType::Ocean);
+        // An even split between sea and land falls back to land.
+        let split = Grid::from_fn(2, 2, |x, _| if x == 0 { -0.9 } else { 0.2 });
+        assert_eq!(classify_tile(&split, 2, 0, 0), TileType::Land);
+        // Only the requested tile's pixels count.
+        let two_tiles = Grid::from_fn(4, 2, |x, _| if x < 2 { -0.9 } else { 0.2 });
+        assert_eq!(classify_tile(&two_tiles, 2, 0, 0), TileType::Ocean);
+        assert_eq!(classify_tile(&two_tiles, 2, 1, 0), TileType::Land);
+        // A single pixel decides a one-pixel tile.
+        let pixel = |height: f32| Grid::from_fn(1, 1, |_, _| height);
+        assert_eq!(classify_tile(&pixel(-0.9), 1, 0, 0), TileType::Ocean);
+        assert_eq!(classify_tile(&pixel(-0.45), 1, 0, 0), TileType::Shore);
+        assert_eq!(classify_tile(&pixel(0.2), 1, 0, 0), TileType::Land);
+    }
+
+    #[test]
+    fn clamped_heights_classify_as_their_tile() {
+        for tile in [TileType::Ocean, TileType::Shore, TileType::Land] {
+            for step in 0..=40 {
+                let height = -1.0 + step as f32 * 0.05;
+                assert_eq!(classify_noise(clamp_to_band(tile, height) as f64), tile);
+            }
+        }
+    }
+
+    #[test]
+    fn colourize_noise_interpolates_between_bands() {
+        let bands = ALPINE.bands;
+        let (low, high) = (&bands[1], &bands[2]);
+        assert_eq!(colourize_noise(&ALPINE, low.height), low.color.to_array());
+        let middle = (low.height + high.height) / 2.0;
+        assert_eq!(
+            colourize_noise(&ALPINE, middle),
+            low.color.lerp(&high.color, 0.5)
+        );
+        // Heights outside the bands take the nearest end colour.
+        assert_eq!(colourize_noise(&ALPINE, -10.0), bands[0].color.to_array());
+        assert_eq!(
+            colourize_noise(&ALPINE, 10.0),
+            bands[bands.len() - 1].color.to_array()
+        );
+    }
+
+    #[test]
+    fn pick_spawn_entry_respects_max_count() {
+        let entries = [
+            SpawnEntry {
+                max_count: Some(2),
+                ..SpawnEntry::new("rare.png", 100.0)
+            },
+            SpawnEntry::new("common.png", 1.0),
+        ];
+        let mut rng = seeded_rng(5, RngStream::Nature);
+        let mut counts = [0; 2];
+        for _ in 0..50 {
+            let index = pick_spawn_entry(&entries, &counts, &mut rng).unwrap();
+            counts[index] += 1;
+        }
+        assert_eq!(counts, [2, 48]);
+
+        let limited = [SpawnEntry {
+            max_count: Some(1),
+            ..SpawnEntry::new("once.png", 1.0)
+        }];
+        assert_eq!(pick_spawn_entry(&limited, &[0], &mut rng), Some(0));
+        assert_eq!(pick_spawn_entry(&limited, &[1], &mut rng), None);
+    }
+
+    #[test]
+    fn land_fraction_sets_the_share_of_pixels_above_sea() {
+        // Heights spread evenly over [-1, 1).
+        let heightmap = Grid::from_fn(100, 100, |x, y| (y * 100 + x) as f32 / 5000.0 - 1.0);
+        for land_fraction in [0.25, 0.5, 0.9] {
+            let thresholds =
+                thresholds_for_land_fraction(&heightmap, land_fraction, &ALPINE.thresholds);
+            let expected_sea = 1.0 - 2.0 * land_fraction;
+            assert!((thresholds.sea - expected_sea).abs() < 0.01);
+            assert!(
+                (thresholds.land
+                    - thresholds.sea
+                    - (ALPINE.thresholds.land - ALPINE.thresholds.sea))
+                    .abs()
+                    < 1e-9
+            );
+        }
+    }
+
+    #[test]
+    fn rivers_never_run_uphill() {
+        let config = config(64, 64, 8);
+        for seed in 0..3 {
+            let data = MapData::generate(seed, &config);
+            let elevations = tile_elevations(&data.heightmap, config.tile_size);
+            let drainage = drain(&elevations, &data.tiles);
+            for y in 0..config.height {
+                for x in 0..config.width {
+                    if *data.tiles.get(x, y) != TileType::River {
+                        continue;
+                    }
+                    if let Some((dx, dy)) = *drainage.downstream.get(x, y) {
+                        assert!(elevations.get(dx, dy) <= elevations.get(x, y));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_region_is_smaller_than_the_minimum() {
+        let config = config(64, 64, 8);
+        for seed in 0..4 {
+            let data = MapData::generate(seed, &config);
+            for region in &label_regions(&data.tiles).regions {
+                assert!(
+                    region.size >= config.min_region_size,
+                    "seed {}: {:?} region of {} tiles",
+                    seed,
+                    region.kind,
+                    region.size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cliffs_appear_only_when_asked_for() {
+        let cliffs = |config: &MapConfig, seed| {
+            let data = MapData::generate(seed, config);
+            let tiles = &data.tiles;
+            (0..tiles.height)
+                .flat_map(|y| (0..tiles.width).map(move |x| (x, y)))
+                .filter(|&(x, y)| *tiles.get(x, y) == TileType::Cliff)
+                .count()
+        };
+        let plain = config(64, 64, 8);
+        let steep = MapConfig {
+            cliff_share: Some(0.04),
+            ..plain.clone()
+        };
+        for seed in 0..3 {
+            assert_eq!(cliffs(&plain, seed), 0);
+            assert!(cliffs(&steep, seed) > 0, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn generates_tiny_maps() {
+        for (width, height) in [(1, 1), (3, 3), (5, 2)] {
+            for seed in 0..4 {
+                let data = MapData::generate(seed, &config(width, height, 1));
+                assert_eq!((data.tiles.width, data.tiles.height), (width, height));
+                assert_eq!(
+                    (data.heightmap.width, data.heightmap.height),
+                    (width, height)
+                );
+                // With one pixel per tile, water tiles are exactly the pixels below sea level.
+                for y in 0..height {
+                    for x in 0..width {
+                        let below_sea = (*data.heightmap.get(x, y) as f64) < SEA_THRESHOLD;
+                        let water =
+                            matches!(data.tiles.get(x, y), TileType::Ocean | TileType::Lake);
+                        assert_eq!(water, below_sea, "seed {} tile ({}, {})", seed, x, y);
+                    }
+                }
+                data.pixel_color(width - 1, height - 1);
+            }
+        }
+    }
+}
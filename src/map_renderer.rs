@@ -1,118 +1,57 @@
-use crate::biomes::*;
+use crate::hillshade::unpack_shade;
 use crate::map_components::*;
+use crate::map_generator::*;
+use crate::regions::RegionKind;
 use bevy::prelude::*;
-use noise::{NoiseFn, Perlin};
 use rand::Rng;
 
-const TILE_SIZE: u32 = 32;
-const WIDTH: u32 = 100;
-const HEIGHT: u32 = 100;
-
-const SEA_THRESHOLD: f64 = -0.5;
-const SHORE_THRESHOLD: f64 = -0.4;
-const LAND_THRESHOLD: f64 = -0.38;
-
-fn get_random_biome(rng: &mut rand::rngs::ThreadRng) -> &'static Biome {
-    match rng.gen_range(0..4) {
-        0 => &ALPINE,
-        1 => &DESERT,
-        2 => &TUNDRA,
-        _ => &ALIEN,
-    }
-}
-
-pub fn generate_tile_map(rng: &mut rand::rngs::ThreadRng) -> Vec<Vec<f64>> {
-    let perlin = Perlin::new(rng.gen());
-    let mut tile_map = Vec::with_capacity(HEIGHT as usize);
-
-    for y in 0..HEIGHT {
-        let mut row = Vec::with_capacity(WIDTH as usize);
-        for x in 0..WIDTH {
-            let noise = perlin.get([x as f64 * 0.1, y as f64 * 0.1]);
-            row.push(noise);
-        }
-        tile_map.push(row);
-    }
-    tile_map
+/// Sprites on lower rows are drawn over those behind them.
+fn sprite_depth(config: &MapConfig, tile_y: u32) -> f32 {
+    1.0 + tile_y as f32 / config.height as f32 * 0.5
 }
 
-pub fn colourize_noise(biome: &Biome, noise: f64) -> [u8; 4] {
-    let color = match noise {
-        n if n < SEA_THRESHOLD => [
-            biome.sea_color.r,
-            biome.sea_color.g,
-            biome.sea_color.b,
-            biome.sea_color.a,
-        ],
-        n if n < SHORE_THRESHOLD => {
-            let t = (n - SEA_THRESHOLD) / (SHORE_THRESHOLD - SEA_THRESHOLD);
-            let r = (biome.sea_color.r as f64 * (1.0 - t) + biome.shore_color.r as f64 * t) as u8;
-            let g = (biome.sea_color.g as f64 * (1.0 - t) + biome.shore_color.g as f64 * t) as u8;
-            let b = (biome.sea_color.b as f64 * (1.0 - t) + biome.shore_color.b as f64 * t) as u8;
-            [r, g, b, 255]
-        }
-        n if n < LAND_THRESHOLD => {
-            let t = (n - SHORE_THRESHOLD) / (LAND_THRESHOLD - SHORE_THRESHOLD);
-            let r = (biome.shore_color.r as f64 * (1.0 - t) + biome.land_color.r as f64 * t) as u8;
-            let g = (biome.shore_color.g as f64 * (1.0 - t) + biome.land_color.g as f64 * t) as u8;
-            let b = (biome.shore_color.b as f64 * (1.0 - t) + biome.land_color.b as f64 * t) as u8;
-            [r, g, b, 255]
-        }
-        _ => [
-            biome.land_color.r,
-            biome.land_color.g,
-            biome.land_color.b,
-            biome.land_color.a,
-        ],
-    };
-    color
+fn tile_translation(config: &MapConfig, tile_x: u32, tile_y: u32, z: f32) -> Vec3 {
+    let (x, y) = config.tile_center(tile_x, tile_y);
+    Vec3::new(x, y, z)
 }
 
-pub fn render_map(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
-    let mut rng = rand::thread_rng();
-    let biome = get_random_biome(&mut rng);
-    let perlin = Perlin::new(rng.gen());
-    
-    let mut land_entities = Vec::with_capacity((WIDTH * HEIGHT) as usize);
-    let mut sea_entities = Vec::with_capacity((WIDTH * HEIGHT) as usize);
-    let mut shore_entities = Vec::with_capacity((WIDTH * HEIGHT) as usize);
-
-    for tile_y in 0..HEIGHT {
-        for tile_x in 0..WIDTH {
-            let mut tile_data = Vec::with_capacity((TILE_SIZE * TILE_SIZE * 4) as usize);
-            let mut sea_count = 0;
-            let mut shore_count = 0;
-            let mut land_count = 0;
-
-            // Extract tile pixels from full image and count tile types
-            for tile_pixel_y in 0..TILE_SIZE {
-                for tile_pixel_x in 0..TILE_SIZE {
-                    let pixel_x = tile_x * TILE_SIZE + tile_pixel_x;
-                    let pixel_y = tile_y * TILE_SIZE + tile_pixel_y;
-
-                    let noise =
-                        perlin.get([pixel_x as f64 * 0.0008, pixel_y as f64 * 0.0008]);
-                    let colourized_pixel = colourize_noise(biome, noise);
-
-                    let [r, g, b, a] = [colourized_pixel[0], colourized_pixel[1], colourized_pixel[2], colourized_pixel[3]];
-
-                    match (r, g, b) {
-                        (sr, sg, sb)
-                            if sr == biome.sea_color.r
-                                && sg == biome.sea_color.g
-                                && sb == biome.sea_color.b =>
-                        {
-                            sea_count += 1
-                        }
-                        (lr, lg, lb)
-                            if lr == biome.land_color.r
-                                && lg == biome.land_color.g
-                                && lb == biome.land_color.b =>
-                        {
-                            land_count += 1
-                        }
-                        _ => shore_count += 1,
-                    }
+pub fn render_map(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    seed: Res<MapSeed>,
+    config: Res<MapConfig>,
+) {
+    let data = MapData::generate(seed.0, &config);
+    let mut rng = seed.rng(RngStream::Texture);
+    let tile_size = config.tile_size;
+    let tile_count = (config.width * config.height) as usize;
+
+    let mut land_entities = Vec::with_capacity(tile_count);
+    let mut ocean_entities = Vec::with_capacity(tile_count);
+    let mut lake_entities = Vec::with_capacity(tile_count);
+    let mut shore_entities = Vec::with_capacity(tile_count);
+    let mut river_entities = Vec::with_capacity(tile_count);
+    let mut mountain_entities = Vec::with_capacity(tile_count);
+    let mut cliff_entities = Vec::with_capacity(tile_count);
+
+    for tile_y in 0..config.height {
+        for tile_x in 0..config.width {
+            let mut tile_data = Vec::with_capacity((tile_size * tile_size * 4) as usize);
+
+            for tile_pixel_y in 0..tile_size {
+                for tile_pixel_x in 0..tile_size {
+                    let pixel_x = tile_x * tile_size + tile_pixel_x;
+                    let pixel_y = tile_y * tile_size + tile_pixel_y;
+
+                    let [r, g, b, a] = data.pixel_color(pixel_x, pixel_y);
+                    // Water lies flat, whatever the relief under it.
+                    let is_water = *data.heightmap.get(pixel_x, pixel_y) < SEA_THRESHOLD as f32
+                        || *data.river_pixels.get(pixel_x, pixel_y);
+                    let shade = match &data.shading {
+                        Some(shading) if !is_water => unpack_shade(*shading.get(pixel_x, pixel_y)),
+                        _ => 1.0,
+                    };
+                    let [r, g, b] = [r, g, b].map(|channel| (channel as f32 * shade).min(255.0) as u8);
 
                     let variation = rng.gen_range(-8..=8);
                     let new_r = (r as i16 + variation).clamp(0, 255) as u8;
@@ -123,13 +62,10 @@ pub fn render_map(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
                 }
             }
 
-            let total_pixels = (TILE_SIZE * TILE_SIZE) as u32;
-            let threshold = total_pixels / 2;
-
             let tile_image = Image::new(
                 bevy::render::render_resource::Extent3d {
-                    width: TILE_SIZE,
-                    height: TILE_SIZE,
+                    width: tile_size,
+                    height: tile_size,
                     depth_or_array_layers: 1,
                 },
                 bevy::render::render_resource::TextureDimension::D2,
@@ -137,127 +73,132 @@ pub fn render_map(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
                 bevy::render::render_resource::TextureFormat::Rgba8UnormSrgb,
             );
 
-            let tile_handle = images.add(tile_image);
-
-            match (sea_count >= threshold, shore_count >= threshold, land_count >= threshold) {
-                (true, false, false) => sea_entities.push((
-                    SpriteBundle {
-                        texture: tile_handle,
-                        transform: Transform::from_translation(Vec3::new(
-                            tile_x as f32 * TILE_SIZE as f32 + TILE_SIZE as f32 / 2.0,
-                            (HEIGHT - 1 - tile_y) as f32 * TILE_SIZE as f32
-                                + TILE_SIZE as f32 / 2.0,
-                            0.0,
-                        )),
-                        ..default()
-                    },
-                    SeaTile,
-                    TilePosition { x: tile_x, y: tile_y },
-                )),
-                (false, true, false) => shore_entities.push((
-                    SpriteBundle {
-                        texture: tile_handle,
-                        transform: Transform::from_translation(Vec3::new(
-                            tile_x as f32 * TILE_SIZE as f32 + TILE_SIZE as f32 / 2.0,
-                            (HEIGHT - 1 - tile_y) as f32 * TILE_SIZE as f32
-                                + TILE_SIZE as f32 / 2.0,
-                            0.0,
-                        )),
-                        ..default()
-                    },
-                    ShoreTile,
-                    TilePosition { x: tile_x, y: tile_y },
-                )),
-                (false, false, true) => land_entities.push((
-                    SpriteBundle {
-                        texture: tile_handle,
-                        transform: Transform::from_translation(Vec3::new(
-                            tile_x as f32 * TILE_SIZE as f32 + TILE_SIZE as f32 / 2.0,
-                            (HEIGHT - 1 - tile_y) as f32 * TILE_SIZE as f32
-                                + TILE_SIZE as f32 / 2.0,
-                            0.0,
-                        )),
-                        ..default()
-                    },
-                    LandTile,
-                    TilePosition { x: tile_x, y: tile_y },
-                )),
-                _ => land_entities.push((
-                    SpriteBundle {
-                        texture: tile_handle,
-                        transform: Transform::from_translation(Vec3::new(
-                            tile_x as f32 * TILE_SIZE as f32 + TILE_SIZE as f32 / 2.0,
-                            (HEIGHT - 1 - tile_y) as f32 * TILE_SIZE as f32
-                                + TILE_SIZE as f32 / 2.0,
-                            0.0,
-                        )),
-                        ..default()
-                    },
-                    LandTile,
-                    TilePosition { x: tile_x, y: tile_y },
-                )),
+            let sprite = SpriteBundle {
+                texture: images.add(tile_image),
+                transform: Transform::from_translation(tile_translation(&config, tile_x, tile_y, 0.0)),
+                ..default()
             };
+            let position = TilePosition { x: tile_x, y: tile_y };
+            let region = *data.regions.ids.get(tile_x, tile_y);
+
+            match data.tiles.get(tile_x, tile_y) {
+                TileType::Ocean => ocean_entities.push((sprite, OceanTile, position, region)),
+                TileType::Lake => lake_entities.push((sprite, LakeTile, position, region)),
+                TileType::Shore => shore_entities.push((sprite, ShoreTile, position, region)),
+                TileType::Land => land_entities.push((sprite, LandTile, position, region)),
+                TileType::River => river_entities.push((sprite, RiverTile, position, region)),
+                TileType::Mountain => mountain_entities.push((sprite, MountainTile, position, region)),
+                TileType::Cliff => cliff_entities.push((sprite, CliffTile, position, region)),
+            }
         }
     }
-    commands.spawn_batch(sea_entities);
+    let largest_landmass = data
+        .regions
+        .regions
+        .iter()
+        .filter(|region| region.kind == RegionKind::Land)
+        .map(|region| region.size)
+        .max()
+        .unwrap_or(0);
+    info!(
+        "map has {} ocean(s), {} lake(s) and {} landmass(es), the largest {} tiles",
+        data.regions.count(RegionKind::Ocean),
+        data.regions.count(RegionKind::Lake),
+        data.regions.count(RegionKind::Land),
+        largest_landmass
+    );
+
+    commands.spawn_batch(ocean_entities);
+    commands.spawn_batch(lake_entities);
     commands.spawn_batch(shore_entities);
     commands.spawn_batch(land_entities);
-    commands.insert_resource(Map { biome });
+    commands.spawn_batch(river_entities);
+    commands.spawn_batch(mountain_entities);
+    commands.spawn_batch(cliff_entities);
+    commands.insert_resource(Map { data });
 }
 
 pub fn render_nature(
     mut commands: Commands,
-    land_tiles: Query<(&Transform, &TilePosition), With<LandTile>>,
     asset_server: Res<AssetServer>,
     map: Res<Map>,
+    seed: Res<MapSeed>,
+    config: Res<MapConfig>,
 ) {
-    let mut rng = rand::thread_rng();
-    let biome = map.biome;
-    let nature_map = generate_tile_map(&mut rng);
-    let mut entities: Vec<_> = Vec::with_capacity((WIDTH * HEIGHT) as usize);
-
-    let rock1 = asset_server.load(&format!("{}{}", biome.asset_path, "rock1.png"));
-    let rock2 = asset_server.load(&format!("{}{}", biome.asset_path, "rock2.png"));
-    let rock3 = asset_server.load(&format!("{}{}", biome.asset_path, "rock3.png"));
-
-    let tree1 = asset_server.load(&format!("{}{}", biome.asset_path, "tree1.png"));
-    let tree2 = asset_server.load(&format!("{}{}", biome.asset_path, "tree2.png"));
-    let tree3 = asset_server.load(&format!("{}{}", biome.asset_path, "tree3.png"));
-
-    println!("Found {} land tiles", land_tiles.iter().count());
-
-    for (transform, tile_pos) in land_tiles.iter() {
-        let noise_value = nature_map[tile_pos.y as usize][tile_pos.x as usize];
-
-        let nature_handle = match noise_value {
-            n if n > 0.1 && n < 0.11 => Some(match rng.gen_range(1..=3) {
-                1 => rock1.clone(),
-                2 => rock2.clone(),
-                _ => rock3.clone(),
-            }),
-            n if n > 0.8 => Some(match rng.gen_range(1..=3) {
-                1 => tree1.clone(),
-                2 => tree2.clone(),
-                _ => tree3.clone(),
-            }),
-            _ => None,
-        };
+    let mut entities: Vec<_> = Vec::with_capacity((config.width * config.height) as usize);
+    // Drawn in row order from their own stream, so the same seed always
+    // dresses the same feature the same way.
+    let mut rng = seed.rng(RngStream::Decoration);
+
+    for tile_y in 0..config.height {
+        for tile_x in 0..config.width {
+            let Some(feature) = map.data.nature.get(tile_x, tile_y) else {
+                continue;
+            };
+            let nature_handle = asset_server.load(format!(
+                "{}{}",
+                feature.biome.asset_path,
+                feature.asset
+            ));
+
+            let (x, y) = config.footprint_center(tile_x, tile_y, feature.size);
+            let size = (config.tile_size * feature.size) as f32;
+            let max_offset = config.tile_size as f32 * 0.15;
+            let offset = Vec2::new(
+                rng.gen_range(-max_offset..=max_offset),
+                rng.gen_range(-max_offset..=max_offset),
+            );
+            let rotation = if feature.rotates {
+                rng.gen_range(-12.0f32..=12.0).to_radians()
+            } else {
+                0.0
+            };
+            let scale = rng.gen_range(0.85..=1.15);
+            let brightness = rng.gen_range(0.85..=1.0);
+            let warmth = rng.gen_range(-0.04..=0.04);
+            let z = sprite_depth(&config, tile_y);
 
-        if let Some(handle) = nature_handle {
             entities.push((SpriteBundle {
-                texture: handle,
-                transform: Transform::from_translation(Vec3::new(
-                    transform.translation.x,
-                    transform.translation.y,
-                    1.0,
-                )),
+                texture: nature_handle,
+                transform: Transform::from_xyz(x + offset.x, y + offset.y, z)
+                    .with_rotation(Quat::from_rotation_z(rotation))
+                    .with_scale(Vec3::splat(scale)),
                 sprite: Sprite {
-                    custom_size: Some(Vec2::new(TILE_SIZE as f32, TILE_SIZE as f32)),
+                    custom_size: Some(Vec2::splat(size)),
+                    flip_x: rng.gen(),
+                    color: Color::rgb(
+                        brightness + warmth,
+                        brightness,
+                        brightness - warmth,
+                    ),
                     ..default()
                 },
                 ..default()
-            }, Nature, TilePosition { x: tile_pos.x, y: tile_pos.y }));
+            }, Nature, TilePosition { x: tile_x, y: tile_y }, Footprint { size: feature.size }));
         }
     }
     commands.spawn_batch(entities);
 }
+
+pub fn render_points_of_interest(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    map: Res<Map>,
+    config: Res<MapConfig>,
+) {
+    let entities: Vec<_> = map.data.points_of_interest.iter().map(|poi| {
+        let size = poi.kind.size();
+        let (x, y) = config.footprint_center(poi.x, poi.y, size);
+        (SpriteBundle {
+            texture: asset_server.load(format!("{}{}", poi.biome.asset_path, poi.kind.asset())),
+            transform: Transform::from_xyz(x, y, sprite_depth(&config, poi.y)),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat((config.tile_size * size) as f32)),
+                ..default()
+            },
+            ..default()
+        }, *poi, TilePosition { x: poi.x, y: poi.y }, Footprint { size })
+    }).collect();
+    info!("placed {} points of interest", entities.len());
+    commands.spawn_batch(entities);
+}
@@ -1,14 +1,25 @@
 use bevy::prelude::*;
 use crate::tank::{Unit, Selectable, Selected};
-use crate::map_components::{SeaTile, Nature};
+use crate::map_components::{OceanTile, LakeTile, MountainTile, CliffTile, Nature, TilePosition, Footprint, Map};
+use crate::map_generator::MapConfig;
+use crate::settlements::PointOfInterest;
+
+/// Speed multiplier for units driving on a road.
+const ROAD_SPEED_BONUS: f32 = 1.5;
+
+type ImpassableFilter = (
+    Or<(With<OceanTile>, With<LakeTile>, With<MountainTile>, With<CliffTile>)>,
+    Without<Unit>,
+);
+
+/// Things standing on the map whose whole footprint blocks movement.
+type ObstacleFilter = Or<(With<Nature>, With<PointOfInterest>)>;
 
 #[derive(Component)]
 pub struct MoveTarget {
     pub target: Vec2,
 }
 
-const TILE_SIZE: u32 = 32;
-
 pub fn set_move_target(
     mut commands: Commands,
     mouse_input: Res<Input<MouseButton>>,
@@ -16,6 +27,7 @@ pub fn set_move_target(
     camera_query: Query<(&Camera, &GlobalTransform)>,
     selected_units: Query<Entity, (With<Selected>, With<Selectable>)>,
     selectable_query: Query<&Transform, (With<Selectable>, Without<Selected>)>,
+    config: Res<MapConfig>,
 ) {
     if mouse_input.just_pressed(MouseButton::Left) {
         let window = windows.single();
@@ -29,7 +41,7 @@ pub fn set_move_target(
                     let unit_pos = transform.translation.truncate();
                     let distance = (world_pos - unit_pos).length();
                     
-                    if distance < TILE_SIZE as f32 / 2.0 {
+                    if distance < config.tile_size as f32 / 2.0 {
                         clicked_on_unit = true;
                         break;
                     }
@@ -50,9 +62,12 @@ pub fn move_units(
     mut commands: Commands,
     time: Res<Time>,
     mut units: Query<(Entity, &mut Transform, &Unit, &MoveTarget)>,
-    sea_tiles: Query<&Transform, (With<SeaTile>, Without<Unit>)>,
-    nature_objects: Query<&Transform, (With<Nature>, Without<Unit>)>,
+    blocked_tiles: Query<&Transform, ImpassableFilter>,
+    obstacles: Query<(&TilePosition, &Footprint), ObstacleFilter>,
+    map: Res<Map>,
+    config: Res<MapConfig>,
 ) {
+    let tile_size = config.tile_size as f32;
     for (entity, mut transform, unit, move_target) in units.iter_mut() {
         let current_pos = transform.translation.truncate();
         let target_pos = move_target.target;
@@ -60,26 +75,32 @@ pub fn move_units(
         
         if distance > 1.0 {
             let direction = (target_pos - current_pos).normalize();
-            let move_distance = unit.speed * time.delta_seconds();
+            let on_road = config
+                .tile_at(current_pos.into())
+                .is_some_and(|(tile_x, tile_y)| *map.data.roads.get(tile_x, tile_y));
+            let speed = if on_road { unit.speed * ROAD_SPEED_BONUS } else { unit.speed };
+            let move_distance = speed * time.delta_seconds();
             let new_pos = current_pos + direction * move_distance;
             
-            // Check if new position would collide with sea or nature
+            // Check if new position would collide with impassable terrain or nature
             let mut can_move = true;
             
-            // Check sea tiles
-            for sea_transform in sea_tiles.iter() {
-                let sea_pos = sea_transform.translation.truncate();
-                if (new_pos - sea_pos).length() < TILE_SIZE as f32 {
+            // Check water, mountain and cliff tiles
+            for blocked_transform in blocked_tiles.iter() {
+                let blocked_pos = blocked_transform.translation.truncate();
+                if (new_pos - blocked_pos).length() < tile_size {
                     can_move = false;
                     break;
                 }
             }
             
-            // Check nature objects
+            // Check nature and points of interest against every tile of their footprint
             if can_move {
-                for nature_transform in nature_objects.iter() {
-                    let nature_pos = nature_transform.translation.truncate();
-                    if (new_pos - nature_pos).length() < TILE_SIZE as f32 {
+                for (obstacle_tile, footprint) in obstacles.iter() {
+                    let center = Vec2::from(config.footprint_center(obstacle_tile.x, obstacle_tile.y, footprint.size));
+                    let half_span = Vec2::splat((footprint.size - 1) as f32 * tile_size / 2.0);
+                    let nearest_tile_center = new_pos.clamp(center - half_span, center + half_span);
+                    if (new_pos - nearest_tile_center).length() < tile_size {
                         can_move = false;
                         break;
                     }
@@ -0,0 +1,181 @@
+//! Tunable noise sources shared by the terrain and nature layers.
+
+use noise::core::worley::ReturnType;
+use noise::{
+    Fbm, MultiFractal, NoiseFn, OpenSimplex, Perlin, RidgedMulti, Seedable, Value, Worley,
+};
+
+#[derive(Clone, Copy)]
+pub enum NoiseSource {
+    Perlin,
+    /// Smoother than Perlin with fewer axis-aligned artefacts.
+    OpenSimplex,
+    /// Interpolated random lattice values; blocky at low octave counts.
+    Value,
+    /// Distance to the nearest cell point, giving cellular patterns.
+    Worley,
+    /// Average of several sources, each seeded independently.
+    Combined(&'static [NoiseSource]),
+}
+
+#[derive(Clone, Copy)]
+pub enum Fractal {
+    /// A single octave of the base noise.
+    Single,
+    /// Fractal Brownian motion: octaves summed with falling amplitude.
+    Fbm,
+    /// Ridged multifractal: sharp crests, good for mountain chains and fjords.
+    RidgedMulti,
+}
+
+#[derive(Clone, Copy)]
+pub struct NoiseSettings {
+    pub source: NoiseSource,
+    pub fractal: Fractal,
+    pub octaves: usize,
+    /// Frequency multiplier between successive octaves.
+    pub lacunarity: f64,
+    /// Amplitude multiplier between successive octaves; higher is rougher.
+    pub persistence: f64,
+    /// Cycles per sample unit (pixel for terrain, tile for nature).
+    pub frequency: f64,
+    /// Shift applied in noise space, after scaling by `frequency`.
+    pub offset: [f64; 2],
+}
+
+pub struct NoiseLayer {
+    source: Box<dyn NoiseFn<f64, 2>>,
+    frequency: f64,
+    offset: [f64; 2],
+}
+
+impl NoiseLayer {
+    pub fn new(settings: &NoiseSettings, seed: u32) -> Self {
+        NoiseLayer {
+            source: build_source(settings, settings.source, seed),
+            frequency: settings.frequency,
+            offset: settings.offset,
+        }
+    }
+
+    pub fn get(&self, x: f64, y: f64) -> f64 {
+        self.source.get([
+            x * self.frequency + self.offset[0],
+            y * self.frequency + self.offset[1],
+        ])
+    }
+}
+
+/// Offsets sample coordinates by a second noise field before sampling the
+/// terrain, bending straight contours into swirls and fjords.
+#[derive(Clone, Copy)]
+pub struct DomainWarp {
+    /// Maximum displacement, in sample units.
+    pub strength: f64,
+    /// Frequency of the displacement field, in cycles per sample unit.
+    pub frequency: f64,
+}
+
+pub struct WarpLayer {
+    x: NoiseLayer,
+    y: NoiseLayer,
+    strength: f64,
+}
+
+impl WarpLayer {
+    pub fn new(warp: &DomainWarp, seed: u32) -> Self {
+        let settings = NoiseSettings {
+            source: NoiseSource::Perlin,
+            fractal: Fractal::Fbm,
+            octaves: 3,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            frequency: warp.frequency,
+            offset: [0.0, 0.0],
+        };
+        // Fbm seeds its octaves consecutively, so keep the two axes well apart.
+        WarpLayer {
+            x: NoiseLayer::new(&settings, seed),
+            y: NoiseLayer::new(&settings, seed.wrapping_add(1013)),
+            strength: warp.strength,
+        }
+    }
+
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            x + self.x.get(x, y) * self.strength,
+            y + self.y.get(x, y) * self.strength,
+        )
+    }
+}
+
+fn build_source(
+    settings: &NoiseSettings,
+    source: NoiseSource,
+    seed: u32,
+) -> Box<dyn NoiseFn<f64, 2>> {
+    match source {
+        NoiseSource::Perlin => build_fractal(settings, seed, Perlin::new),
+        NoiseSource::OpenSimplex => build_fractal(settings, seed, OpenSimplex::new),
+        NoiseSource::Value => build_fractal(settings, seed, Value::new),
+        NoiseSource::Worley => build_fractal(settings, seed, |seed| {
+            Worley::new(seed).set_return_type(ReturnType::Distance)
+        }),
+        NoiseSource::Combined(sources) => Box::new(Average(
+            sources
+                .iter()
+                .enumerate()
+                .map(|(i, source)| {
+                    build_source(settings, *source, seed.wrapping_add(i as u32 * 7919))
+                })
+                .collect(),
+        )),
+    }
+}
+
+/// Wraps a base noise in the configured fractal. Octave sources are built
+/// with `make` so per-source options (such as Worley's return type) survive.
+fn build_fractal<T>(
+    settings: &NoiseSettings,
+    seed: u32,
+    make: impl Fn(u32) -> T,
+) -> Box<dyn NoiseFn<f64, 2>>
+where
+    T: Default + Seedable + NoiseFn<f64, 2> + 'static,
+{
+    let octave_sources = |octaves: usize| {
+        (0..octaves as u32)
+            .map(|i| make(seed.wrapping_add(i)))
+            .collect()
+    };
+
+    match settings.fractal {
+        Fractal::Single => Box::new(make(seed)),
+        Fractal::Fbm => {
+            let fbm = Fbm::<T>::new(seed)
+                .set_frequency(1.0)
+                .set_octaves(settings.octaves)
+                .set_lacunarity(settings.lacunarity)
+                .set_persistence(settings.persistence);
+            let sources = octave_sources(fbm.octaves);
+            Box::new(fbm.set_sources(sources))
+        }
+        Fractal::RidgedMulti => {
+            let ridged = RidgedMulti::<T>::new(seed)
+                .set_frequency(1.0)
+                .set_octaves(settings.octaves)
+                .set_lacunarity(settings.lacunarity)
+                .set_persistence(settings.persistence);
+            let sources = octave_sources(ridged.octaves);
+            Box::new(ridged.set_sources(sources))
+        }
+    }
+}
+
+struct Average(Vec<Box<dyn NoiseFn<f64, 2>>>);
+
+impl NoiseFn<f64, 2> for Average {
+    fn get(&self, point: [f64; 2]) -> f64 {
+        self.0.iter().map(|source| source.get(point)).sum::<f64>() / self.0.len() as f64
+    }
+}
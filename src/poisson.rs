@@ -0,0 +1,91 @@
+//! Poisson-disc sampling (Bridson's algorithm), used to scatter nature evenly
+//! without clumping.
+
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// Candidates tried around each active point before it is retired.
+const ATTEMPTS: u32 = 30;
+
+/// Points in `[0, width) x [0, height)` no closer to each other than `spacing`.
+pub fn poisson_disc(width: f32, height: f32, spacing: f32, rng: &mut impl Rng) -> Vec<(f32, f32)> {
+    // Cells as wide as the spacing, so any point too close to a candidate
+    // lies in the 3x3 block of cells around it.
+    let cell_size = spacing;
+    let columns = (width / cell_size).ceil() as usize + 1;
+    let rows = (height / cell_size).ceil() as usize + 1;
+    let mut cells: Vec<Vec<usize>> = vec![Vec::new(); columns * rows];
+    let cell_of = |x: f32, y: f32| ((x / cell_size) as usize, (y / cell_size) as usize);
+
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+    let first = (rng.gen_range(0.0..width), rng.gen_range(0.0..height));
+    let (column, row) = cell_of(first.0, first.1);
+    cells[row * columns + column].push(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let active_index = rng.gen_range(0..active.len());
+        let (x, y) = points[active[active_index]];
+        let mut placed = false;
+
+        for _ in 0..ATTEMPTS {
+            let angle = rng.gen_range(0.0..TAU);
+            let distance = rng.gen_range(spacing..spacing * 2.0);
+            let candidate = (x + angle.cos() * distance, y + angle.sin() * distance);
+            if candidate.0 < 0.0
+                || candidate.1 < 0.0
+                || candidate.0 >= width
+                || candidate.1 >= height
+            {
+                continue;
+            }
+            let (column, row) = cell_of(candidate.0, candidate.1);
+            let too_close = (row.saturating_sub(1)..=(row + 1).min(rows - 1)).any(|near_row| {
+                (column.saturating_sub(1)..=(column + 1).min(columns - 1)).any(|near_column| {
+                    cells[near_row * columns + near_column]
+                        .iter()
+                        .any(|&index| {
+                            let (other_x, other_y) = points[index];
+                            let (dx, dy) = (other_x - candidate.0, other_y - candidate.1);
+                            dx * dx + dy * dy < spacing * spacing
+                        })
+                })
+            });
+            if !too_close {
+                cells[row * columns + column].push(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                placed = true;
+                break;
+            }
+        }
+        if !placed {
+            active.swap_remove(active_index);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn points_keep_their_spacing_and_stay_in_bounds() {
+        for (width, height, spacing) in [(40.0, 30.0, 2.5), (7.0, 90.0, 4.0), (1.0, 1.0, 3.0)] {
+            let points = poisson_disc(width, height, spacing, &mut StdRng::seed_from_u64(3));
+            assert!(!points.is_empty());
+            for (index, &(x, y)) in points.iter().enumerate() {
+                assert!((0.0..width).contains(&x) && (0.0..height).contains(&y));
+                for &(other_x, other_y) in &points[index + 1..] {
+                    let (dx, dy) = (other_x - x, other_y - y);
+                    assert!(dx * dx + dy * dy >= spacing * spacing);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Flood-fill labelling of the tile map into connected water bodies and landmasses.
+
+use crate::grid::Grid;
+use crate::map_generator::TileType;
+
+/// Index of a tile's region in `Regions::regions`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RegionId(pub u32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RegionKind {
+    /// Water connected to the map border.
+    Ocean,
+    /// Water enclosed by land on every side.
+    Lake,
+    Land,
+}
+
+pub struct Region {
+    pub kind: RegionKind,
+    /// Number of tiles in the region.
+    pub size: u32,
+}
+
+pub struct Regions {
+    pub ids: Grid<RegionId>,
+    pub regions: Vec<Region>,
+}
+
+impl Regions {
+    pub fn region_at(&self, x: u32, y: u32) -> &Region {
+        &self.regions[self.ids.get(x, y).0 as usize]
+    }
+
+    pub fn count(&self, kind: RegionKind) -> usize {
+        self.regions
+            .iter()
+            .filter(|region| region.kind == kind)
+            .count()
+    }
+}
+
+/// Labels 4-connected runs of water and of land. Water is every ocean or
+/// lake tile; whether a water region is an ocean is decided by whether it
+/// reaches the map border, so the incoming water tiles may be of either kind.
+pub fn label_regions(tiles: &Grid<TileType>) -> Regions {
+    let (width, height) = (tiles.width, tiles.height);
+    let mut ids: Grid<Option<RegionId>> = Grid::from_fn(width, height, |_, _| None);
+    let mut regions = Vec::new();
+    let mut stack = Vec::new();
+
+    for start_y in 0..height {
+        for start_x in 0..width {
+            if ids.get(start_x, start_y).is_some() {
+                continue;
+            }
+            let id = RegionId(regions.len() as u32);
+            let is_water = tiles.get(start_x, start_y).is_water();
+            let mut size = 0;
+            let mut touches_border = false;
+
+            ids.set(start_x, start_y, Some(id));
+            stack.push((start_x, start_y));
+            while let Some((x, y)) = stack.pop() {
+                size += 1;
+                touches_border |= x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                for (nx, ny) in tiles.neighbours4(x, y) {
+                    if ids.get(nx, ny).is_none() && tiles.get(nx, ny).is_water() == is_water {
+                        ids.set(nx, ny, Some(id));
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+
+            let kind = match (is_water, touches_border) {
+                (false, _) => RegionKind::Land,
+                (true, true) => RegionKind::Ocean,
+                (true, false) => RegionKind::Lake,
+            };
+            regions.push(Region { kind, size });
+        }
+    }
+
+    Regions {
+        ids: Grid::from_fn(width, height, |x, y| {
+            ids.get(x, y).expect("every tile is labelled")
+        }),
+        regions,
+    }
+}
+
+/// Groups the 4-connected cells of a `width` x `height` grid for which
+/// `include` holds, listing each group's cells in row order.
+pub fn connected_areas(
+    width: u32,
+    height: u32,
+    include: impl Fn(u32, u32) -> bool,
+) -> Vec<Vec<(u32, u32)>> {
+    let mut visited = Grid::from_fn(width, height, |x, y| !include(x, y));
+    let mut areas = Vec::new();
+    let mut stack = Vec::new();
+
+    for start_y in 0..height {
+        for start_x in 0..width {
+            if *visited.get(start_x, start_y) {
+                continue;
+            }
+            let mut area = Vec::new();
+            visited.set(start_x, start_y, true);
+            stack.push((start_x, start_y));
+            while let Some((x, y)) = stack.pop() {
+                area.push((x, y));
+                for (nx, ny) in visited.neighbours4(x, y) {
+                    if !*visited.get(nx, ny) {
+                        visited.set(nx, ny, true);
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            area.sort_by_key(|&(x, y)| (y, x));
+            areas.push(area);
+        }
+    }
+    areas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn water_is_ocean_only_where_it_reaches_the_border() {
+        // A bay on the left edge and a pond enclosed by land, both given as
+        // ocean tiles so the kind has to come from the border test.
+        let rows = ["~~....", "~..~~.", "...~~.", "......"];
+        let tiles = Grid::from_fn(6, 4, |x, y| match rows[y as usize].as_bytes()[x as usize] {
+            b'~' => TileType::Ocean,
+            _ => TileType::Land,
+        });
+        let regions = label_regions(&tiles);
+
+        assert_eq!(regions.region_at(0, 0).kind, RegionKind::Ocean);
+        assert_eq!(regions.region_at(0, 0).size, 3);
+        assert_eq!(regions.region_at(3, 1).kind, RegionKind::Lake);
+        assert_eq!(regions.region_at(4, 2).size, 4);
+        assert_eq!(regions.region_at(5, 3).kind, RegionKind::Land);
+        assert_eq!(
+            (
+                regions.count(RegionKind::Ocean),
+                regions.count(RegionKind::Lake),
+                regions.count(RegionKind::Land)
+            ),
+            (1, 1, 1)
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! Road network: least-cost paths over the tile grid between settlements,
+//! rasterised into a soft-edged pixel mask the tile textures blend with.
+
+use crate::grid::Grid;
+use crate::map_generator::TileType;
+use crate::regions::connected_areas;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Cost multiplier for tiles a road already runs over, so later roads join
+/// the network instead of running alongside it.
+const REUSE_DISCOUNT: f32 = 0.4;
+/// Extra cost per unit of elevation change between neighbouring tiles.
+const SLOPE_COST: f32 = 40.0;
+/// Pixels over which a road's edge fades into the terrain.
+const EDGE_SOFTNESS: f32 = 2.0;
+
+#[derive(Clone, Copy)]
+pub struct RoadSettings {
+    /// Road width as a fraction of the tile size.
+    pub width: f32,
+}
+
+impl Default for RoadSettings {
+    fn default() -> Self {
+        RoadSettings { width: 0.35 }
+    }
+}
+
+/// Base cost of building over a tile; `None` where no road can go.
+fn terrain_cost(tile: TileType) -> Option<f32> {
+    match tile {
+        TileType::Land => Some(1.0),
+        TileType::Shore => Some(2.0),
+        // Fordable, but roads cross only to avoid a long detour.
+        TileType::River => Some(6.0),
+        TileType::Ocean | TileType::Lake | TileType::Mountain | TileType::Cliff => None,
+    }
+}
+
+struct OpenTile {
+    estimate: f32,
+    x: u32,
+    y: u32,
+}
+
+impl PartialEq for OpenTile {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenTile {}
+
+impl PartialOrd for OpenTile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenTile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.estimate
+            .total_cmp(&other.estimate)
+            .then((self.y, self.x).cmp(&(other.y, other.x)))
+    }
+}
+
+/// Connects `endpoints` with a spanning tree of roads, shortest links first,
+/// skipping pairs no road can join. Roads only enter `blocked` tiles where
+/// they end. Returns the tiles roads run over and each pixel's road
+/// coverage from 0 to 255.
+pub fn build_roads(
+    tiles: &Grid<TileType>,
+    elevations: &Grid<f32>,
+    blocked: &Grid<bool>,
+    endpoints: &[(u32, u32)],
+    settings: &RoadSettings,
+    tile_size: u32,
+) -> (Grid<bool>, Grid<u8>) {
+    let (width, height) = (tiles.width, tiles.height);
+    let mut roads = Grid::from_fn(width, height, |_, _| false);
+    let mut pixels = Grid::from_fn(width * tile_size, height * tile_size, |_, _| 0);
+
+    let mut area_of = Grid::from_fn(width, height, |_, _| usize::MAX);
+    let areas = connected_areas(width, height, |x, y| {
+        terrain_cost(*tiles.get(x, y)).is_some()
+    });
+    for (index, area) in areas.iter().enumerate() {
+        for &(x, y) in area {
+            area_of.set(x, y, index);
+        }
+    }
+
+    let mut links = Vec::new();
+    for (i, &(ax, ay)) in endpoints.iter().enumerate() {
+        for (j, &(bx, by)) in endpoints.iter().enumerate().skip(i + 1) {
+            if *area_of.get(ax, ay) != usize::MAX && area_of.get(ax, ay) == area_of.get(bx, by) {
+                let (dx, dy) = (ax.abs_diff(bx) as f32, ay.abs_diff(by) as f32);
+                links.push(((dx * dx + dy * dy).sqrt(), i, j));
+            }
+        }
+    }
+    links.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Kruskal's algorithm: a link is built only if its ends are not yet
+    // joined, which leaves a tree of roads without redundant loops.
+    let mut network: Vec<usize> = (0..endpoints.len()).collect();
+    for (_, i, j) in links {
+        let (root_i, root_j) = (root(&mut network, i), root(&mut network, j));
+        if root_i == root_j {
+            continue;
+        }
+        let Some(path) = find_path(
+            tiles,
+            elevations,
+            blocked,
+            &roads,
+            endpoints[i],
+            endpoints[j],
+        ) else {
+            continue;
+        };
+        network[root_i] = root_j;
+        for &(x, y) in &path {
+            roads.set(x, y, true);
+        }
+        paint_road(
+            &mut pixels,
+            &path,
+            settings.width * tile_size as f32 / 2.0,
+            tile_size,
+        );
+    }
+
+    (roads, pixels)
+}
+
+/// The endpoint standing for every endpoint already joined to `index`.
+fn root(network: &mut [usize], mut index: usize) -> usize {
+    while network[index] != index {
+        network[index] = network[network[index]];
+        index = network[index];
+    }
+    index
+}
+
+/// A* over 8-connected tiles. Diagonal steps only pass between open ground,
+/// so roads never clip a coastline's corner and ford rivers across a river
+/// tile rather than slipping between two.
+fn find_path(
+    tiles: &Grid<TileType>,
+    elevations: &Grid<f32>,
+    blocked: &Grid<bool>,
+    roads: &Grid<bool>,
+    start: (u32, u32),
+    goal: (u32, u32),
+) -> Option<Vec<(u32, u32)>> {
+    let (width, height) = (tiles.width, tiles.height);
+    let mut cost_to = Grid::from_fn(width, height, |_, _| f32::INFINITY);
+    let mut came_from = Grid::from_fn(width, height, |_, _| None);
+    let mut open = BinaryHeap::new();
+    // Octile distance at the cheapest possible per-tile cost, so it never
+    // overestimates and the path found is the cheapest.
+    let heuristic = |x: u32, y: u32| {
+        let (dx, dy) = (x.abs_diff(goal.0) as f32, y.abs_diff(goal.1) as f32);
+        (dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)) * REUSE_DISCOUNT
+    };
+    let open_ground = |x: u32, y: u32| matches!(tiles.get(x, y), TileType::Land | TileType::Shore);
+
+    cost_to.set(start.0, start.1, 0.0);
+    open.push(Reverse(OpenTile {
+        estimate: heuristic(start.0, start.1),
+        x: start.0,
+        y: start.1,
+    }));
+    while let Some(Reverse(OpenTile { estimate, x, y })) = open.pop() {
+        if (x, y) == goal {
+            let mut path = vec![goal];
+            while let Some(previous) =
+                *came_from.get(path[path.len() - 1].0, path[path.len() - 1].1)
+            {
+                path.push(previous);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let cost = *cost_to.get(x, y);
+        if estimate > cost + heuristic(x, y) {
+            continue;
+        }
+        for (nx, ny) in tiles.neighbours8(x, y) {
+            if *blocked.get(nx, ny) && (nx, ny) != goal {
+                continue;
+            }
+            let Some(base) = terrain_cost(*tiles.get(nx, ny)) else {
+                continue;
+            };
+            let diagonal = nx != x && ny != y;
+            if diagonal && !(open_ground(nx, y) && open_ground(x, ny)) {
+                continue;
+            }
+            let climb = (elevations.get(nx, ny) - elevations.get(x, y)).abs();
+            let mut step = (base + climb * SLOPE_COST)
+                * if diagonal {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.0
+                };
+            if *roads.get(nx, ny) {
+                step *= REUSE_DISCOUNT;
+            }
+            let next = cost + step;
+            if next < *cost_to.get(nx, ny) {
+                cost_to.set(nx, ny, next);
+                came_from.set(nx, ny, Some((x, y)));
+                open.push(Reverse(OpenTile {
+                    estimate: next + heuristic(nx, ny),
+                    x: nx,
+                    y: ny,
+                }));
+            }
+        }
+    }
+    None
+}
+
+/// Rounds the path's corners with two passes of Chaikin's corner cutting,
+/// then strokes it through the tile centres with a soft edge.
+fn paint_road(pixels: &mut Grid<u8>, path: &[(u32, u32)], radius: f32, tile_size: u32) {
+    let mut points: Vec<(f32, f32)> = path
+        .iter()
+        .map(|&(x, y)| {
+            (
+                (x * tile_size) as f32 + tile_size as f32 / 2.0,
+                (y * tile_size) as f32 + tile_size as f32 / 2.0,
+            )
+        })
+        .collect();
+    for _ in 0..2 {
+        if points.len() < 3 {
+            break;
+        }
+        let mut smoothed = vec![points[0]];
+        for pair in points.windows(2) {
+            let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+            smoothed.push((ax * 0.75 + bx * 0.25, ay * 0.75 + by * 0.25));
+            smoothed.push((ax * 0.25 + bx * 0.75, ay * 0.25 + by * 0.75));
+        }
+        smoothed.push(points[points.len() - 1]);
+        points = smoothed;
+    }
+
+    for pair in points.windows(2) {
+        paint_segment(pixels, pair[0], pair[1], radius);
+    }
+}
+
+fn paint_segment(pixels: &mut Grid<u8>, start: (f32, f32), end: (f32, f32), radius: f32) {
+    let reach = radius + EDGE_SOFTNESS;
+    let min_x = (start.0.min(end.0) - reach).max(0.0) as u32;
+    let min_y = (start.1.min(end.1) - reach).max(0.0) as u32;
+    let max_x = ((start.0.max(end.0) + reach) as u32).min(pixels.width - 1);
+    let max_y = ((start.1.max(end.1) + reach) as u32).min(pixels.height - 1);
+    let (segment_x, segment_y) = (end.0 - start.0, end.1 - start.1);
+    let length_squared = (segment_x * segment_x + segment_y * segment_y).max(f32::EPSILON);
+
+    for pixel_y in min_y..=max_y {
+        for pixel_x in min_x..=max_x {
+            let (px, py) = (
+                pixel_x as f32 + 0.5 - start.0,
+                pixel_y as f32 + 0.5 - start.1,
+            );
+            let t = ((px * segment_x + py * segment_y) / length_squared).clamp(0.0, 1.0);
+            let (dx, dy) = (px - segment_x * t, py - segment_y * t);
+            let distance = (dx * dx + dy * dy).sqrt();
+            let coverage = ((radius - distance) / EDGE_SOFTNESS + 0.5).clamp(0.0, 1.0);
+            let value = (coverage * 255.0) as u8;
+            if value > *pixels.get(pixel_x, pixel_y) {
+                pixels.set(pixel_x, pixel_y, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roads_keep_to_open_unblocked_ground() {
+        // A wall down the middle of every kind of impassable tile, with one
+        // gap blocked by a footprint and one left open.
+        let wall = [
+            TileType::Ocean,
+            TileType::Lake,
+            TileType::Mountain,
+            TileType::Cliff,
+            TileType::Mountain,
+            TileType::Lake,
+            TileType::Land,
+            TileType::Land,
+        ];
+        let tiles = Grid::from_fn(12, 8, |x, y| match (x, y) {
+            (5, _) => wall[y as usize],
+            (8, 2..=5) => TileType::River,
+            (_, 0) => TileType::Shore,
+            _ => TileType::Land,
+        });
+        let elevations = Grid::from_fn(12, 8, |x, y| (x + y) as f32 * 0.01);
+        let endpoints = [(1, 3), (10, 3), (10, 0)];
+        let blocked = Grid::from_fn(12, 8, |x, y| {
+            (x, y) == (5, 6) || (x == 3 && y < 7) || endpoints.contains(&(x, y))
+        });
+        let allowed = |x: u32, y: u32| {
+            terrain_cost(*tiles.get(x, y)).is_some()
+                && (!*blocked.get(x, y) || endpoints.contains(&(x, y)))
+        };
+
+        let path = find_path(
+            &tiles,
+            &elevations,
+            &blocked,
+            &Grid::from_fn(12, 8, |_, _| false),
+            endpoints[0],
+            endpoints[1],
+        )
+        .expect("the open gap joins both sides");
+        assert_eq!(
+            (path[0], path[path.len() - 1]),
+            (endpoints[0], endpoints[1])
+        );
+        assert!(path.iter().all(|&(x, y)| allowed(x, y)));
+        assert!(path.contains(&(5, 7)));
+
+        let (roads, _) = build_roads(
+            &tiles,
+            &elevations,
+            &blocked,
+            &endpoints,
+            &RoadSettings::default(),
+            4,
+        );
+        for y in 0..8 {
+            for x in 0..12 {
+                assert!(!*roads.get(x, y) || allowed(x, y), "road on ({}, {})", x, y);
+            }
+        }
+        assert!(endpoints.iter().all(|&(x, y)| *roads.get(x, y)));
+    }
+}
@@ -0,0 +1,216 @@
+//! Placement of points of interest: villages, outposts, oil derricks and
+//! ruins, each put on the free land that suits its kind best.
+
+use crate::biomes::Biome;
+use crate::grid::Grid;
+use crate::map_generator::{TileType, LAND_THRESHOLD};
+use rand::Rng;
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoiKind {
+    Village,
+    Outpost,
+    OilDerrick,
+    Ruins,
+}
+
+impl PoiKind {
+    /// Placed in this order, so the pickiest kinds claim their sites first.
+    const PLACEMENT_ORDER: [PoiKind; 4] = [
+        PoiKind::Village,
+        PoiKind::OilDerrick,
+        PoiKind::Outpost,
+        PoiKind::Ruins,
+    ];
+
+    /// File name inside the biome's asset folder.
+    pub fn asset(self) -> &'static str {
+        match self {
+            PoiKind::Village => "village.png",
+            PoiKind::Outpost => "outpost.png",
+            PoiKind::OilDerrick => "derrick.png",
+            PoiKind::Ruins => "ruins.png",
+        }
+    }
+
+    /// Side of the square footprint, in tiles.
+    pub fn size(self) -> u32 {
+        match self {
+            PoiKind::Village | PoiKind::Ruins => 2,
+            PoiKind::Outpost | PoiKind::OilDerrick => 1,
+        }
+    }
+
+    /// Fraction of a map's points of interest that are of this kind.
+    fn share(self) -> f32 {
+        match self {
+            PoiKind::Village => 0.4,
+            PoiKind::Outpost | PoiKind::OilDerrick | PoiKind::Ruins => 0.2,
+        }
+    }
+
+    /// How well a site suits this kind, from 0 (not at all) to about 1.
+    fn suitability(self, site: &Site) -> f32 {
+        let flatness = (1.0 - site.slope / 0.1).clamp(0.0, 1.0);
+        match self {
+            // Flat lowland a short walk from water.
+            PoiKind::Village => {
+                let water_distance = site.water_distance as f32;
+                let near_water = (1.0 - ((water_distance - 3.0) / 6.0).powi(2)).max(0.0);
+                near_water * 0.5 + flatness * 0.3 + (1.0 - site.elevation) * 0.2
+            }
+            // High ground overlooking the land around it.
+            PoiKind::Outpost => site.elevation * 0.7 + flatness * 0.3,
+            // Dry, flat basins far from any water.
+            PoiKind::OilDerrick => {
+                (site.water_distance as f32 / 12.0).min(1.0) * 0.6 + flatness * 0.4
+            }
+            // Anywhere; spacing and chance decide.
+            PoiKind::Ruins => 0.5,
+        }
+    }
+}
+
+/// A placed point of interest and the biome whose sprites it uses.
+#[derive(Clone, Copy)]
+pub struct PointOfInterest {
+    pub kind: PoiKind,
+    pub biome: &'static Biome,
+    /// Top-left tile of the footprint.
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Clone, Copy)]
+pub struct PoiSettings {
+    /// Map tiles per point of interest, so larger maps get more of them.
+    pub tiles_per_poi: u32,
+    /// Fewest tiles between the footprints of any two points of interest.
+    pub spacing: u32,
+}
+
+impl Default for PoiSettings {
+    fn default() -> Self {
+        PoiSettings {
+            tiles_per_poi: 400,
+            spacing: 8,
+        }
+    }
+}
+
+/// What the suitability scores look at for a candidate tile.
+struct Site {
+    /// Tiles to the nearest sea, lake or river, 4-connected.
+    water_distance: u32,
+    /// Largest elevation difference to a neighbouring tile.
+    slope: f32,
+    /// Height within the land band: 0 at the shore, 1 at the mountains.
+    elevation: f32,
+}
+
+/// Places each kind greedily on its best-scoring land, with a seeded jitter
+/// so equally good sites are picked differently per seed. Footprints only
+/// cover land tiles and keep `spacing` clear of every earlier placement.
+/// Elevations are judged against `mountain_level`, where mountains begin.
+pub fn place_points_of_interest(
+    tiles: &Grid<TileType>,
+    elevations: &Grid<f32>,
+    mountain_level: f32,
+    biomes: &Grid<&'static Biome>,
+    settings: &PoiSettings,
+    rng: &mut impl Rng,
+) -> Vec<PointOfInterest> {
+    let water_distances = water_distances(tiles);
+    let site_at = |x: u32, y: u32| {
+        let elevation = *elevations.get(x, y);
+        Site {
+            water_distance: *water_distances.get(x, y),
+            slope: elevations
+                .neighbours8(x, y)
+                .map(|(nx, ny)| (elevations.get(nx, ny) - elevation).abs())
+                .fold(0.0, f32::max),
+            elevation: ((elevation - LAND_THRESHOLD as f32)
+                / (mountain_level - LAND_THRESHOLD as f32).max(f32::EPSILON))
+            .clamp(0.0, 1.0),
+        }
+    };
+    let area = (tiles.width * tiles.height) as f32;
+    let mut placed: Vec<PointOfInterest> = Vec::new();
+
+    for kind in PoiKind::PLACEMENT_ORDER {
+        let count = (area * kind.share() / settings.tiles_per_poi.max(1) as f32).round() as usize;
+        let size = kind.size();
+        let mut candidates = Vec::new();
+        for y in 0..tiles.height.saturating_sub(size - 1) {
+            for x in 0..tiles.width.saturating_sub(size - 1) {
+                let on_land = (y..y + size).all(|footprint_y| {
+                    (x..x + size)
+                        .all(|footprint_x| *tiles.get(footprint_x, footprint_y) == TileType::Land)
+                });
+                if !on_land {
+                    continue;
+                }
+                let score = kind.suitability(&site_at(x, y)) + rng.gen_range(0.0..0.25);
+                candidates.push((score, x, y));
+            }
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut remaining = count;
+        for (_, x, y) in candidates {
+            if remaining == 0 {
+                break;
+            }
+            let clear = placed.iter().all(|other| {
+                gap(x, size, other.x, other.kind.size()) >= settings.spacing
+                    || gap(y, size, other.y, other.kind.size()) >= settings.spacing
+            });
+            if clear {
+                placed.push(PointOfInterest {
+                    kind,
+                    biome: biomes.get(x, y),
+                    x,
+                    y,
+                });
+                remaining -= 1;
+            }
+        }
+    }
+    placed
+}
+
+/// Tiles strictly between two spans along one axis; 0 where they overlap or touch.
+fn gap(start: u32, size: u32, other_start: u32, other_size: u32) -> u32 {
+    if start >= other_start + other_size {
+        start - (other_start + other_size)
+    } else {
+        other_start.saturating_sub(start + size)
+    }
+}
+
+/// Breadth-first distance from every tile to the nearest water or river
+/// tile; `u32::MAX` everywhere on a map with none.
+fn water_distances(tiles: &Grid<TileType>) -> Grid<u32> {
+    let mut distances = Grid::from_fn(tiles.width, tiles.height, |_, _| u32::MAX);
+    let mut queue = VecDeque::new();
+    for y in 0..tiles.height {
+        for x in 0..tiles.width {
+            let tile = *tiles.get(x, y);
+            if tile.is_water() || tile == TileType::River {
+                distances.set(x, y, 0);
+                queue.push_back((x, y));
+            }
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let next = distances.get(x, y) + 1;
+        for (nx, ny) in tiles.neighbours4(x, y) {
+            if *distances.get(nx, ny) > next {
+                distances.set(nx, ny, next);
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    distances
+}
@@ -0,0 +1,118 @@
+//! Large-scale shaping masks blended into the terrain noise before tiles are
+//! classified, so a map can be forced into an island, continent or coastline.
+
+use rand::Rng;
+use std::str::FromStr;
+
+#[derive(Clone, Copy)]
+pub enum Side {
+    North,
+    South,
+    East,
+    West,
+}
+
+#[derive(Clone, Copy)]
+pub enum ShapeMask {
+    /// One island centred on the map, with sea along every edge.
+    Island,
+    /// Several smaller islands scattered across open sea.
+    Archipelago { islands: u32 },
+    /// Land along every edge, so only inland water remains.
+    Continent,
+    /// Open sea along one side of the map and land on the rest.
+    CoastalStrip { sea_side: Side },
+}
+
+#[derive(Clone, Copy)]
+pub struct MapShape {
+    pub mask: ShapeMask,
+    /// Scale of the bias added to the noise; 0 leaves the terrain untouched.
+    pub strength: f64,
+}
+
+impl FromStr for ShapeMask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "island" => Ok(ShapeMask::Island),
+            "archipelago" => Ok(ShapeMask::Archipelago { islands: 5 }),
+            "continent" => Ok(ShapeMask::Continent),
+            "coast-north" => Ok(ShapeMask::CoastalStrip {
+                sea_side: Side::North,
+            }),
+            "coast-south" => Ok(ShapeMask::CoastalStrip {
+                sea_side: Side::South,
+            }),
+            "coast-east" => Ok(ShapeMask::CoastalStrip {
+                sea_side: Side::East,
+            }),
+            "coast-west" => Ok(ShapeMask::CoastalStrip {
+                sea_side: Side::West,
+            }),
+            _ => Err(format!("unknown map shape: {}", s)),
+        }
+    }
+}
+
+pub struct MaskLayer {
+    shape: MapShape,
+    /// Archipelago island centres in normalised map coordinates.
+    centres: Vec<(f64, f64)>,
+}
+
+impl MaskLayer {
+    pub fn new(shape: &MapShape, rng: &mut impl Rng) -> Self {
+        let centres = match shape.mask {
+            ShapeMask::Archipelago { islands } => (0..islands)
+                .map(|_| (rng.gen_range(0.15..0.85), rng.gen_range(0.15..0.85)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        MaskLayer {
+            shape: *shape,
+            centres,
+        }
+    }
+
+    /// Biases `noise` by the mask at normalised position (`u`, `v`), where `v`
+    /// runs from the north edge (0) to the south edge (1).
+    pub fn apply(&self, noise: f64, u: f64, v: f64) -> f64 {
+        noise + self.bias(u, v) * self.shape.strength
+    }
+
+    /// Negative values push towards sea and positive towards land. The forced
+    /// regions reach ±1.5, beyond the range of the noise, so they always win.
+    fn bias(&self, u: f64, v: f64) -> f64 {
+        let dx = u * 2.0 - 1.0;
+        let dy = v * 2.0 - 1.0;
+
+        match self.shape.mask {
+            ShapeMask::Island => {
+                let d = (dx * dx + dy * dy).sqrt().min(1.0);
+                0.5 - 2.0 * d * d
+            }
+            ShapeMask::Archipelago { islands } => {
+                let radius = 0.6 / (islands.max(1) as f64).sqrt();
+                self.centres
+                    .iter()
+                    .map(|&(cx, cy)| {
+                        let d = ((u - cx).powi(2) + (v - cy).powi(2)).sqrt() / radius;
+                        0.5 - 2.0 * d * d
+                    })
+                    .fold(-1.5, f64::max)
+            }
+            ShapeMask::Continent => 1.5 * dx.abs().max(dy.abs()).powi(4),
+            ShapeMask::CoastalStrip { sea_side } => {
+                let distance_from_sea = match sea_side {
+                    Side::North => v,
+                    Side::South => 1.0 - v,
+                    Side::West => u,
+                    Side::East => 1.0 - u,
+                };
+                ((distance_from_sea - 0.3) * 5.0).clamp(-1.5, 0.3)
+            }
+        }
+    }
+}
@@ -1,9 +1,8 @@
 use crate::map_components::*;
+use crate::map_generator::{MapConfig, RngStream};
 use bevy::prelude::*;
 use rand::Rng;
 
-const TILE_SIZE: u32 = 32;
-
 #[derive(Component)]
 pub struct Unit {
     pub health: i32,
@@ -21,39 +20,41 @@ pub struct HealthBar;
 
 pub fn spawn_tank(
     mut commands: Commands,
-    land_tiles: Query<(&Transform, &TilePosition), With<LandTile>>,
-    nature: Query<&TilePosition, With<Nature>>,
+    map: Res<Map>,
     asset_server: Res<AssetServer>,
+    seed: Res<MapSeed>,
+    config: Res<MapConfig>,
 ) {
-    let nature_positions: Vec<(u32, u32)> = nature.iter().map(|pos| (pos.x, pos.y)).collect();
-
-    let available_tiles: Vec<(&Transform, &TilePosition)> = land_tiles
-        .iter()
-        .filter(|(_, tile_pos)| !nature_positions.contains(&(tile_pos.x, tile_pos.y)))
-        .collect();
-
-    if !available_tiles.is_empty() {
-        let mut rng = rand::thread_rng();
-        let random_index = rng.gen_range(0..available_tiles.len());
-        let (transform, _) = available_tiles[random_index];
-
-        let tank_texture = asset_server.load("vehicles/tank.png");
-
-        let tank_pos = Vec3::new(transform.translation.x, transform.translation.y, 2.0);
-        
-        commands.spawn((
-            SpriteBundle {
-                texture: tank_texture,
-                transform: Transform::from_translation(tank_pos),
-                sprite: Sprite {
-                    custom_size: Some(Vec2::new(TILE_SIZE as f32, TILE_SIZE as f32)),
-                    ..default()
-                },
+    // Only the largest connected landmass is used, so the tank can never
+    // start stranded on an island it cannot leave.
+    let available_tiles = match map.data.spawn_area(config.min_spawn_area) {
+        Ok(tiles) => tiles,
+        Err(error) => {
+            error!("cannot spawn tank: {}", error);
+            return;
+        }
+    };
+
+    let mut rng = seed.rng(RngStream::Spawn);
+    let random_index = rng.gen_range(0..available_tiles.len());
+    let (tile_x, tile_y) = available_tiles[random_index];
+    let (x, y) = config.tile_center(tile_x, tile_y);
+
+    let tank_texture = asset_server.load("vehicles/tank.png");
+
+    let tank_pos = Vec3::new(x, y, 2.0);
+    
+    commands.spawn((
+        SpriteBundle {
+            texture: tank_texture,
+            transform: Transform::from_translation(tank_pos),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(config.tile_size as f32)),
                 ..default()
             },
-            Unit{health: 100, speed: 80.0},
-            Selectable,
-        ));
-    }
+            ..default()
+        },
+        Unit{health: 100, speed: 80.0},
+        Selectable,
+    ));
 }
-